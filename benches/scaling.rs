@@ -0,0 +1,70 @@
+//! Times `pack` with each built-in strategy on random one- and
+//! three-dimensional instances of growing size, to show how packing time
+//! grows with the number of items.
+//!
+//! Run with `cargo bench`; pass a number to stop at that many items, e.g.
+//! `cargo bench -- 100000`.
+//!
+//! With one dimension the bin index finds each bin in logarithmic time, so
+//! ten times the items should take little more than ten times as long. With
+//! three it cannot rule out every subtree the item fits nowhere in, and
+//! time grows faster; see `BinIndex` in `src/index.rs`.
+
+use std::time::Instant;
+
+use bin_packer::{pack, AlmostWorstFit, BestFit, FirstFit, Item, NextFit, PackOptions, PlacementStrategy, Problem, ResourceSchema, WorstFit};
+
+const SIZES: [usize; 4] = [1_000, 10_000, 100_000, 1_000_000];
+const CAPACITY: [u32; 3] = [64, 256, 1000];
+const WEIGHTS: [f32; 3] = [0.4, 0.4, 0.2];
+
+fn main() {
+    let max_items = std::env::args().skip(1).find_map(|arg| arg.parse::<usize>().ok()).unwrap_or(usize::MAX);
+
+    println!("{:>10} {:>10} {:>15} {:>10} {:>10} {:>12}", "dimensions", "items", "strategy", "bins", "bound", "time");
+    for dimensions in [1, 3] {
+        for &n in SIZES.iter().filter(|&&n| n <= max_items) {
+            let problem = instance(n, dimensions, n as u64);
+            run(&problem, FirstFit);
+            run(&problem, BestFit);
+            run(&problem, WorstFit);
+            run(&problem, AlmostWorstFit);
+            run(&problem, NextFit);
+        }
+    }
+}
+
+fn run(problem: &Problem, strategy: impl PlacementStrategy + 'static) {
+    let name = format!("{:?}", strategy);
+    let dimensions = problem.schema.len();
+    let options = PackOptions::new(WEIGHTS[..dimensions].to_vec()).with_strategy(strategy);
+    let start = Instant::now();
+    let result = pack(problem, &options).expect("instance is valid");
+    let elapsed = start.elapsed();
+    println!(
+        "{:>10} {:>10} {:>15} {:>10} {:>10} {:>12.3?}",
+        dimensions,
+        problem.items.len(),
+        name,
+        result.bins_used(),
+        result.lower_bound.expect("strict capacity mode"),
+        elapsed
+    );
+}
+
+// `n` items with demands in the first `dimensions` of cores, memory and
+// disk drawn uniformly up to half a bin, from a SplitMix64 stream seeded
+// with `seed`.
+fn instance(n: usize, dimensions: usize, seed: u64) -> Problem {
+    let mut state = seed;
+    let mut next = move |bound: u64| {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        ((z ^ (z >> 31)) % bound) as u32 + 1
+    };
+    let schema = ResourceSchema::new(["cores", "memory_gb", "disk_gb"][..dimensions].iter().copied());
+    let items = (0..n).map(|i| Item::new(format!("item-{}", i), [next(32), next(128), next(500)][..dimensions].to_vec()));
+    Problem::new(schema, CAPACITY[..dimensions].to_vec()).with_items(items)
+}
@@ -0,0 +1,270 @@
+use crate::bin::Bin;
+use crate::budget::Budget;
+use crate::error::PackError;
+use crate::ffd::{self, pack};
+use crate::options::{CapacityMode, PackOptions};
+use crate::problem::Problem;
+use crate::result::PackResult;
+use crate::rng::Rng;
+
+/// Settings for [`anneal`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnnealOptions {
+    /// Seed for the random moves; the same seed and iteration budget give the
+    /// same packing.
+    pub seed: u64,
+    /// When to stop. Must set at least one limit, since the cooling schedule
+    /// runs over the budget.
+    pub budget: Budget,
+    /// Temperature at the start of the run, in units of one bin.
+    pub initial_temperature: f64,
+    /// Temperature at the end of the run.
+    pub final_temperature: f64,
+    /// Probability of trying a swap of two items rather than moving one.
+    pub swap_probability: f64,
+}
+
+impl Default for AnnealOptions {
+    fn default() -> Self {
+        AnnealOptions {
+            seed: 0,
+            budget: Budget::iterations(1_000_000),
+            initial_temperature: 0.005,
+            final_temperature: 0.00005,
+            swap_probability: 0.5,
+        }
+    }
+}
+
+impl AnnealOptions {
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn with_budget(mut self, budget: Budget) -> Self {
+        self.budget = budget;
+        self
+    }
+}
+
+/// Packs `problem` by simulated annealing over item-to-bin assignments,
+/// for instances too large for [`solve_exact`](crate::solve_exact).
+///
+/// The search starts from the packing [`pack`] finds with `options` and
+/// repeatedly moves a random item to another open bin or swaps two items
+/// between bins, never exceeding a capacity. The search minimises the sum
+/// over open bins of cost times `1 - fill²`, where fill is the weighted
+/// fraction of capacity in use: it falls as load is consolidated into fewer,
+/// fuller bins and drops sharply when a bin is emptied. Worse assignments are
+/// accepted with the Metropolis probability at a temperature that cools
+/// geometrically over `anneal.budget`.
+///
+/// The best packing seen is returned: the one with the lowest cost of open
+/// bins (their number, with the default unit cost), ties broken by the
+/// balance term above. Its bins are switched to the cheapest type that
+/// holds their items.
+///
+/// Only items placed by the solver move; items already in existing bins
+/// stay put. `options` must use [`CapacityMode::Strict`], and the problem
+/// must have no constraints and no item may require labels.
+///
+/// ```
+/// use bin_packer::{anneal, pack, AnnealOptions, Budget, Item, PackOptions, Problem, ResourceSchema};
+///
+/// let items = (0..60).map(|i| Item::new(format!("job-{}", i), [1 + i * 5 % 9, 4 + i * 29 % 37]));
+/// let problem = Problem::new(ResourceSchema::new(["cores", "memory_gb"]), [16, 64]).with_items(items);
+/// let options = PackOptions::new([0.5, 0.5]);
+/// let settings = AnnealOptions::default().with_seed(7).with_budget(Budget::iterations(20_000));
+/// let result = anneal(&problem, &options, &settings)?;
+/// assert_eq!(result, anneal(&problem, &options, &settings)?);
+/// assert!(result.bins_used() <= pack(&problem, &options)?.bins_used());
+/// assert_eq!(result.gap(), Some(0));
+/// # Ok::<(), bin_packer::PackError>(())
+/// ```
+pub fn anneal(problem: &Problem, options: &PackOptions, anneal: &AnnealOptions) -> Result<PackResult, PackError> {
+    problem.validate()?;
+    let options = &options.normalized(&problem.schema)?;
+    if matches!(options.capacity_mode, CapacityMode::Weighted) {
+        return Err(PackError::Unsupported("simulated annealing needs the strict capacity mode".to_string()));
+    }
+    if !problem.constraints.is_empty() {
+        return Err(PackError::Unsupported("simulated annealing does not support item constraints".to_string()));
+    }
+    if problem.has_required_labels() {
+        return Err(PackError::Unsupported("simulated annealing does not support required labels".to_string()));
+    }
+    if !anneal.budget.is_limited() {
+        return Err(PackError::Unsupported("simulated annealing needs an iteration or time limit".to_string()));
+    }
+
+    let start = pack(problem, options)?;
+    let mut state = State::new(problem, &start, options);
+    let mut best = (state.open_cost, state.energy);
+    let mut best_assignment = state.assignment.clone();
+
+    let mut rng = Rng::new(anneal.seed);
+    let mut tracker = anneal.budget.start();
+    let cooling = anneal.final_temperature / anneal.initial_temperature;
+    while !state.items.is_empty() && tracker.tick() {
+        let temperature = anneal.initial_temperature * cooling.powf(tracker.progress());
+        let before = state.energy;
+        let Some(undo) = state.random_move(&mut rng, anneal.swap_probability) else { continue };
+
+        let after = state.energy;
+        if after > before && rng.unit() >= ((before - after) / temperature).exp() {
+            state.undo(undo);
+        } else if (state.open_cost, state.energy + 1e-9) < best {
+            best = (state.open_cost, state.energy);
+            best_assignment.clone_from(&state.assignment);
+        }
+    }
+
+    Ok(rebuild(problem, start, &best_assignment, options))
+}
+
+// A move that can be reverted: each item goes back to the bin it came from.
+type Undo = [(usize, usize); 2];
+
+// Assignment of the movable items, with per-bin loads kept up to date.
+struct State<'a> {
+    // Demand of each movable item.
+    items: Vec<&'a [u32]>,
+    assignment: Vec<usize>,
+    limits: Vec<Vec<u64>>,
+    loads: Vec<Vec<u64>>,
+    counts: Vec<usize>,
+    costs: Vec<f64>,
+    existing: usize,
+    weights: Vec<f64>,
+    // Cost of the open new bins.
+    open_cost: f64,
+    // Sum over open new bins of cost * (1 - fill²).
+    energy: f64,
+}
+
+impl<'a> State<'a> {
+    fn new(problem: &Problem, start: &'a PackResult, options: &PackOptions) -> Self {
+        let dimensions = problem.schema.len();
+        let bins = &start.bins;
+        let mut state = State {
+            items: Vec::new(),
+            assignment: Vec::new(),
+            limits: bins.iter().map(|bin| (0..dimensions).map(|d| bin.limit(d, options)).collect()).collect(),
+            loads: bins.iter().map(|bin| (0..dimensions).map(|d| bin.used(d)).collect()).collect(),
+            counts: bins.iter().map(|bin| bin.items().len()).collect(),
+            costs: bins.iter().map(|bin| bin.bin_type.map_or(0.0, |t| problem.bin_types[t].cost)).collect(),
+            existing: start.existing_bins,
+            weights: options.weights.iter().map(|&w| w as f64).collect(),
+            open_cost: 0.0,
+            energy: 0.0,
+        };
+        for (b, bin) in bins.iter().enumerate() {
+            let fixed = if b < start.existing_bins { problem.existing_bins[b].items().len() } else { 0 };
+            for item in &bin.items()[fixed..] {
+                state.items.push(&item.demand);
+                state.assignment.push(b);
+            }
+            state.add_bin_terms(b, 1.0);
+        }
+        state
+    }
+
+    fn fill(&self, b: usize) -> f64 {
+        (0..self.weights.len()).map(|d| self.weights[d] * self.loads[b][d] as f64 / self.limits[b][d].max(1) as f64).sum()
+    }
+
+    // Adds (sign 1.0) or removes (sign -1.0) bin `b`'s share of the cost
+    // and energy, if it is an open new bin.
+    fn add_bin_terms(&mut self, b: usize, sign: f64) {
+        if b >= self.existing && self.counts[b] > 0 {
+            self.open_cost += sign * self.costs[b];
+            self.energy += sign * self.costs[b] * (1.0 - self.fill(b).powi(2));
+        }
+    }
+
+    fn is_open(&self, b: usize) -> bool {
+        b < self.existing || self.counts[b] > 0
+    }
+
+    fn random_move(&mut self, rng: &mut Rng, swap_probability: f64) -> Option<Undo> {
+        let i = rng.below(self.items.len());
+        let from = self.assignment[i];
+        if rng.unit() < swap_probability {
+            let j = rng.below(self.items.len());
+            let to = self.assignment[j];
+            if to == from || !self.swap_fits(i, j) {
+                return None;
+            }
+            self.transfer(i, to);
+            self.transfer(j, from);
+            Some([(i, from), (j, to)])
+        } else {
+            let to = rng.below(self.loads.len());
+            if to == from || !self.is_open(to) || !self.fits(i, to) {
+                return None;
+            }
+            self.transfer(i, to);
+            Some([(i, from), (i, from)])
+        }
+    }
+
+    fn undo(&mut self, undo: Undo) {
+        for (i, b) in undo {
+            if self.assignment[i] != b {
+                self.transfer(i, b);
+            }
+        }
+    }
+
+    fn fits(&self, i: usize, b: usize) -> bool {
+        (0..self.weights.len()).all(|d| self.loads[b][d] + self.items[i][d] as u64 <= self.limits[b][d])
+    }
+
+    fn swap_fits(&self, i: usize, j: usize) -> bool {
+        let (a, c) = (self.assignment[i], self.assignment[j]);
+        (0..self.weights.len()).all(|d| {
+            let (di, dj) = (self.items[i][d] as u64, self.items[j][d] as u64);
+            self.loads[a][d] - di + dj <= self.limits[a][d] && self.loads[c][d] - dj + di <= self.limits[c][d]
+        })
+    }
+
+    // Moves item `i` to bin `to`, keeping loads, cost and energy in step.
+    fn transfer(&mut self, i: usize, to: usize) {
+        let from = self.assignment[i];
+        self.add_bin_terms(from, -1.0);
+        self.add_bin_terms(to, -1.0);
+        for d in 0..self.weights.len() {
+            self.loads[from][d] -= self.items[i][d] as u64;
+            self.loads[to][d] += self.items[i][d] as u64;
+        }
+        self.counts[from] -= 1;
+        self.counts[to] += 1;
+        self.assignment[i] = to;
+        self.add_bin_terms(from, 1.0);
+        self.add_bin_terms(to, 1.0);
+    }
+}
+
+// Rebuilds `start` with the movable items assigned as in `assignment`.
+fn rebuild(problem: &Problem, start: PackResult, assignment: &[usize], options: &PackOptions) -> PackResult {
+    let existing = start.existing_bins;
+    let mut movable = Vec::with_capacity(assignment.len());
+    let mut bins: Vec<Bin> = Vec::with_capacity(start.bins.len());
+    for (b, mut bin) in start.bins.into_iter().enumerate() {
+        let fixed = if b < existing { problem.existing_bins[b].items().len() } else { 0 };
+        movable.extend(bin.split_off_items(fixed));
+        bins.push(bin);
+    }
+    for (item, &b) in movable.into_iter().zip(assignment) {
+        bins[b].push_item(item);
+    }
+
+    ffd::drop_empty_new_bins(&mut bins, existing);
+    ffd::downsize(&mut bins[existing..], problem, options);
+    PackResult {
+        cost: ffd::total_cost(&bins[existing..], problem),
+        bins,
+        ..start
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::bin_type::BinType;
+use crate::item::Item;
+use crate::options::{CapacityMode, PackOptions};
+use crate::resource::ResourceSchema;
+
+/// A dimension in which an item needs more than a bin can provide.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapacityExceeded {
+    /// Name of the dimension in the schema.
+    pub dimension: String,
+    /// What the item asks for.
+    pub demand: u32,
+    /// What is available in the bin, after applying any overcommit ratio.
+    pub available: u64,
+}
+
+impl fmt::Display for CapacityExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} demand {} exceeds available {}", self.dimension, self.demand, self.available)
+    }
+}
+
+/// A server (bin) with a fixed capacity per dimension and the items placed in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bin {
+    /// Index into [`Problem::bin_types`](crate::Problem::bin_types) of the
+    /// type this bin was opened as, if any.
+    pub bin_type: Option<usize>,
+    /// Capacity per dimension, in schema order.
+    pub capacity: Vec<u32>,
+    /// Usage per dimension not accounted for by `items`, e.g. workloads
+    /// already running on a server that the packer does not know about.
+    /// Empty means none.
+    pub reserved: Vec<u32>,
+    /// Key/value labels that items can select on, e.g. `disk_type=ssd`.
+    pub labels: BTreeMap<String, String>,
+    items: Vec<Item>,
+    // Total demand of `items` per dimension, kept in step with them so that
+    // fit checks do not re-sum the items.
+    load: Vec<u64>,
+}
+
+impl Bin {
+    pub fn new(capacity: impl Into<Vec<u32>>) -> Self {
+        let capacity = capacity.into();
+        Bin {
+            bin_type: None,
+            load: vec![0; capacity.len()],
+            capacity,
+            reserved: Vec::new(),
+            labels: BTreeMap::new(),
+            items: Vec::new(),
+        }
+    }
+
+    /// A bin of `capacity` of which `reserved` is already in use.
+    pub fn with_reserved(capacity: impl Into<Vec<u32>>, reserved: impl Into<Vec<u32>>) -> Self {
+        Bin {
+            reserved: reserved.into(),
+            ..Bin::new(capacity)
+        }
+    }
+
+    /// An empty bin of `bin_type`, which sits at `index` in the catalogue.
+    pub fn of_type(index: usize, bin_type: &BinType) -> Self {
+        Bin {
+            bin_type: Some(index),
+            labels: bin_type.labels.clone(),
+            ..Bin::new(bin_type.capacity.clone())
+        }
+    }
+
+    pub fn with_label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+
+    /// This bin holding `items`, which are added without checking capacity,
+    /// e.g. to describe what a running server holds.
+    pub fn with_items(mut self, items: impl IntoIterator<Item = Item>) -> Self {
+        for item in items {
+            self.push_item(item);
+        }
+        self
+    }
+
+    /// The items placed in this bin.
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    /// Whether `item` can be added: the bin carries the labels it requires
+    /// and has room for it under the capacity mode of `options`.
+    pub fn fits(&self, item: &Item, options: &PackOptions) -> bool {
+        self.has_labels(&item.required_labels) && (0..self.capacity.len()).all(|d| self.fits_dimension(item, d, options))
+    }
+
+    /// Whether the bin carries every label of `selector` with its value.
+    pub fn has_labels(&self, selector: &BTreeMap<String, String>) -> bool {
+        selector.iter().all(|(key, value)| self.labels.get(key) == Some(value))
+    }
+
+    /// How many labels of `selector` the bin carries with their value.
+    pub fn matching_labels(&self, selector: &BTreeMap<String, String>) -> usize {
+        selector.iter().filter(|&(key, value)| self.labels.get(key) == Some(value)).count()
+    }
+
+    /// The dimensions in which `item` has no room under the capacity mode of
+    /// `options`; empty if it has room in all of them. Labels are not
+    /// checked.
+    pub fn exceeded(&self, item: &Item, schema: &ResourceSchema, options: &PackOptions) -> Vec<CapacityExceeded> {
+        (0..self.capacity.len())
+            .filter(|&d| !self.fits_dimension(item, d, options))
+            .map(|d| CapacityExceeded {
+                dimension: schema.name(d).to_string(),
+                demand: item.demand[d],
+                available: self.free(d, options),
+            })
+            .collect()
+    }
+
+    /// Adds `item` if it [`fits`](Bin::fits) and reports whether it was added.
+    pub fn add_item(&mut self, item: Item, options: &PackOptions) -> bool {
+        if self.fits(&item, options) {
+            self.push_item(item);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Adds `item` without checking capacity.
+    pub fn push_item(&mut self, item: Item) {
+        if self.load.len() < item.demand.len() {
+            self.load.resize(item.demand.len(), 0);
+        }
+        for (load, &demand) in self.load.iter_mut().zip(&item.demand) {
+            *load += demand as u64;
+        }
+        self.items.push(item);
+    }
+
+    /// Removes and returns the item at `index`, shifting the later items
+    /// down.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of bounds.
+    pub fn remove_item(&mut self, index: usize) -> Item {
+        let item = self.items.remove(index);
+        self.unload(&item);
+        item
+    }
+
+    // A copy of this bin without the items at `positions`.
+    pub(crate) fn without(&self, positions: &[usize]) -> Bin {
+        let rest = Bin {
+            bin_type: self.bin_type,
+            capacity: self.capacity.clone(),
+            reserved: self.reserved.clone(),
+            labels: self.labels.clone(),
+            items: Vec::new(),
+            load: vec![0; self.load.len()],
+        };
+        rest.with_items(self.items.iter().enumerate().filter(|(k, _)| !positions.contains(k)).map(|(_, item)| item.clone()))
+    }
+
+    // Puts `item` at `index` in place of the item there, which is returned.
+    pub(crate) fn replace_item(&mut self, index: usize, item: Item) -> Item {
+        let removed = std::mem::replace(&mut self.items[index], item);
+        self.unload(&removed);
+        for (load, &demand) in self.load.iter_mut().zip(&self.items[index].demand) {
+            *load += demand as u64;
+        }
+        removed
+    }
+
+    /// Removes and returns every item.
+    pub fn take_items(&mut self) -> Vec<Item> {
+        self.split_off_items(0)
+    }
+
+    // Removes and returns the items from `at` on.
+    pub(crate) fn split_off_items(&mut self, at: usize) -> Vec<Item> {
+        let items = self.items.split_off(at);
+        for item in &items {
+            self.unload(item);
+        }
+        items
+    }
+
+    /// Reserved usage plus the total demand of the placed items in
+    /// dimension `d`.
+    pub fn used(&self, d: usize) -> u64 {
+        let reserved = self.reserved.get(d).copied().unwrap_or(0) as u64;
+        reserved + self.load.get(d).copied().unwrap_or(0)
+    }
+
+    /// Capacity left in dimension `d`; 0 if the bin is full or overcommitted.
+    pub fn remaining(&self, d: usize) -> u32 {
+        (self.capacity[d] as u64).saturating_sub(self.used(d)) as u32
+    }
+
+    fn unload(&mut self, item: &Item) {
+        for (load, &demand) in self.load.iter_mut().zip(&item.demand) {
+            *load -= demand as u64;
+        }
+    }
+
+    fn fits_dimension(&self, item: &Item, d: usize, options: &PackOptions) -> bool {
+        let demand = item.demand[d];
+        let used = self.used(d);
+        match &options.capacity_mode {
+            CapacityMode::Weighted => admits(self.free(d, options), demand, d, options),
+            CapacityMode::Strict(_) => used + demand as u64 <= self.limit(d, options),
+        }
+    }
+
+    // Capacity left below the limit in dimension `d`; 0 if overcommitted.
+    pub(crate) fn free(&self, d: usize, options: &PackOptions) -> u64 {
+        self.limit(d, options).saturating_sub(self.used(d))
+    }
+
+    // Weighted fraction of capacity left in each dimension once `item` is
+    // added.
+    pub(crate) fn slack_after(&self, item: &Item, options: &PackOptions) -> f64 {
+        (0..self.capacity.len())
+            .map(|d| {
+                let left = self.limit(d, options) as f64 - (self.used(d) + item.demand[d] as u64) as f64;
+                options.weights[d] as f64 * left.max(0.0) / self.capacity[d] as f64
+            })
+            .sum()
+    }
+
+    // Capacity the fit check compares against in dimension `d`.
+    pub(crate) fn limit(&self, d: usize, options: &PackOptions) -> u64 {
+        match &options.capacity_mode {
+            CapacityMode::Weighted => self.capacity[d] as u64,
+            CapacityMode::Strict(overcommit) => scaled_capacity(self.capacity[d], overcommit.ratio(d)),
+        }
+    }
+}
+
+// Whether `demand` fits in `free` capacity in dimension `d`. Exact for a bin
+// that is within its limit; a bin over its limit still rejects zero demand in
+// strict mode, which only `Bin::fits` sees.
+pub(crate) fn admits(free: u64, demand: u32, d: usize, options: &PackOptions) -> bool {
+    match &options.capacity_mode {
+        CapacityMode::Weighted => free as f32 >= demand as f32 * options.weights[d],
+        CapacityMode::Strict(_) => free >= demand as u64,
+    }
+}
+
+// The free capacity `demand` needs in dimension `d` to pass `admits`.
+pub(crate) fn needed(demand: u32, d: usize, options: &PackOptions) -> f64 {
+    match &options.capacity_mode {
+        CapacityMode::Weighted => demand as f64 * options.weights[d] as f64,
+        CapacityMode::Strict(_) => demand as f64,
+    }
+}
+
+// Capacity usable once an overcommit ratio is applied. The ratio is an f32, so
+// allow for its rounding error before truncating (20 * 1.15 must give 23).
+fn scaled_capacity(capacity: u32, ratio: f32) -> u64 {
+    (capacity as f64 * ratio as f64 * (1.0 + f32::EPSILON as f64)).floor() as u64
+}
@@ -0,0 +1,43 @@
+use std::collections::BTreeMap;
+
+/// A kind of bin that can be opened, e.g. a server SKU.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinType {
+    pub name: String,
+    /// Capacity per dimension, in schema order.
+    pub capacity: Vec<u32>,
+    /// Price of opening one bin of this type.
+    pub cost: f64,
+    /// How many bins of this type may be opened; `None` for no limit.
+    pub max_count: Option<usize>,
+    /// Labels every bin of this type carries, e.g. `zone=a`.
+    pub labels: BTreeMap<String, String>,
+}
+
+impl BinType {
+    /// An unlimited bin type with a unit cost.
+    pub fn new(name: impl Into<String>, capacity: impl Into<Vec<u32>>) -> Self {
+        BinType {
+            name: name.into(),
+            capacity: capacity.into(),
+            cost: 1.0,
+            max_count: None,
+            labels: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_cost(mut self, cost: f64) -> Self {
+        self.cost = cost;
+        self
+    }
+
+    pub fn with_max_count(mut self, max_count: usize) -> Self {
+        self.max_count = Some(max_count);
+        self
+    }
+
+    pub fn with_label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+}
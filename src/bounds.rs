@@ -0,0 +1,150 @@
+use crate::bin::Bin;
+use crate::error::PackError;
+use crate::item::Item;
+use crate::options::{CapacityMode, PackOptions};
+use crate::problem::Problem;
+
+// Each dimension on its own is a one-dimensional bin packing relaxation of
+// the vector problem, so any one-dimensional bound is valid per dimension.
+// With several bin types, pretending every bin is as large as the largest
+// type in each dimension keeps the bounds valid.
+
+/// Lower bounds on the number of new bins needed to pack a problem's items,
+/// per dimension of the schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LowerBounds {
+    /// Total demand, less the free space in existing bins, over bin
+    /// capacity, rounded up.
+    pub l1: Vec<usize>,
+    /// The Martello–Toth L2 bound, which also counts items that cannot share
+    /// a bin. Equal to `l1` when there are existing bins.
+    pub l2: Vec<usize>,
+}
+
+impl LowerBounds {
+    /// The strongest bound over all dimensions.
+    pub fn best(&self) -> usize {
+        self.l1.iter().chain(&self.l2).copied().max().unwrap_or(0)
+    }
+}
+
+/// Computes lower bounds on the number of new bins any packing of
+/// `problem`'s items needs, ignoring items too large for every bin type.
+///
+/// Capacities are taken after applying any overcommit ratio in `options`.
+///
+/// Fails if `options` uses [`CapacityMode::Weighted`]: its fit check admits
+/// items whose demand exceeds the free capacity, so a bin may hold more than
+/// its capacity and no bound is computed.
+///
+/// ```
+/// use bin_packer::{bin_packing_weighted_ffd, lower_bounds, pack, Item, PackError, PackOptions, Problem, ResourceSchema};
+///
+/// // The weighted fit check puts both items in one bin of 10 cores, so no
+/// // bound holds.
+/// let items = [Item::new("a", [6, 10]), Item::new("b", [6, 10])];
+/// let result = bin_packing_weighted_ffd(&items, 10, 200, 0.6, 0.4)?;
+/// assert_eq!((result.bins_used(), result.lower_bound, result.gap()), (1, None, None));
+///
+/// let problem = Problem::new(ResourceSchema::new(["cores", "disk"]), [10, 200]).with_items(items);
+/// assert!(matches!(lower_bounds(&problem, &PackOptions::weighted([0.6, 0.4])), Err(PackError::Unsupported(_))));
+/// assert_eq!(lower_bounds(&problem, &PackOptions::new([0.6, 0.4]))?.best(), 2);
+/// let result = pack(&problem, &PackOptions::new([0.6, 0.4]))?;
+/// assert_eq!((result.bins_used(), result.lower_bound, result.gap()), (2, Some(2), Some(0)));
+/// # Ok::<(), PackError>(())
+/// ```
+pub fn lower_bounds(problem: &Problem, options: &PackOptions) -> Result<LowerBounds, PackError> {
+    problem.validate()?;
+    let options = &options.normalized(&problem.schema)?;
+    if matches!(options.capacity_mode, CapacityMode::Weighted) {
+        return Err(PackError::Unsupported("lower bounds need the strict capacity mode".to_string()));
+    }
+    let empty_bins: Vec<Bin> = problem.bin_types.iter().enumerate().map(|(t, bt)| Bin::of_type(t, bt)).collect();
+    let items: Vec<&Item> = problem
+        .items
+        .iter()
+        .filter(|item| empty_bins.iter().any(|bin| bin.fits(item, options)))
+        .collect();
+    Ok(for_items(&items, problem, options).expect("strict capacity mode"))
+}
+
+/// Bounds on the new bins needed for `items`; `options` must be normalised.
+/// `None` under [`CapacityMode::Weighted`], which can fill a bin past its
+/// capacity.
+pub(crate) fn for_items(items: &[&Item], problem: &Problem, options: &PackOptions) -> Option<LowerBounds> {
+    let dimensions = problem.schema.len();
+    if matches!(options.capacity_mode, CapacityMode::Weighted) {
+        return None;
+    }
+    let empty_bins: Vec<Bin> = problem.bin_types.iter().enumerate().map(|(t, bt)| Bin::of_type(t, bt)).collect();
+    let capacity: Vec<u64> = (0..dimensions)
+        .map(|d| empty_bins.iter().map(|bin| bin.limit(d, options)).max().unwrap_or(1).max(1))
+        .collect();
+
+    let mut l1_bounds = Vec::with_capacity(dimensions);
+    let mut l2_bounds = Vec::with_capacity(dimensions);
+    for (d, &capacity) in capacity.iter().enumerate() {
+        let sizes: Vec<u64> = items.iter().map(|item| item.demand[d] as u64).collect();
+        if problem.existing_bins.is_empty() {
+            l1_bounds.push(l1(&sizes, capacity));
+            l2_bounds.push(l2(&sizes, capacity));
+        } else {
+            let free: u64 = problem
+                .existing_bins
+                .iter()
+                .map(|bin| bin.limit(d, options).saturating_sub(bin.used(d)))
+                .sum();
+            let bound = sizes.iter().sum::<u64>().saturating_sub(free).div_ceil(capacity) as usize;
+            l1_bounds.push(bound);
+            l2_bounds.push(bound);
+        }
+    }
+    Some(LowerBounds {
+        l1: l1_bounds,
+        l2: l2_bounds,
+    })
+}
+
+/// L1 in one dimension: total demand over capacity, rounded up.
+fn l1(sizes: &[u64], capacity: u64) -> usize {
+    sizes.iter().sum::<u64>().div_ceil(capacity) as usize
+}
+
+/// Martello–Toth L2 in one dimension.
+///
+/// For a threshold `a` with `0 <= a <= capacity / 2`, items larger than
+/// `capacity - a` and items larger than half the capacity each need a bin
+/// of their own, and the items between `a` and half the capacity must fit in
+/// the space those bins leave over or open further bins. L2 is the best such
+/// bound over all thresholds; it is never below L1.
+pub(crate) fn l2(sizes: &[u64], capacity: u64) -> usize {
+    let mut sorted = sizes.to_vec();
+    sorted.sort_unstable();
+    // prefix[i] is the total size of the i smallest items.
+    let mut prefix = Vec::with_capacity(sorted.len() + 1);
+    prefix.push(0u64);
+    for &s in &sorted {
+        prefix.push(prefix.last().unwrap() + s);
+    }
+    // Number of items of size at most `limit`.
+    let count_at_most = |limit: u64| sorted.partition_point(|&s| s <= limit);
+
+    let half = capacity / 2;
+    let small = count_at_most(half);
+    let mut thresholds = sorted[..small].to_vec();
+    thresholds.insert(0, 0);
+    thresholds.dedup();
+
+    let mut best = l1(sizes, capacity);
+    for a in thresholds {
+        let large = count_at_most(capacity - a);
+        let j1 = sorted.len() - large;
+        let j2 = large - small;
+        let j2_free = j2 as u64 * capacity - (prefix[large] - prefix[small]);
+        let from = sorted.partition_point(|&s| s < a);
+        let j3_size = prefix[small] - prefix[from];
+        let extra = j3_size.saturating_sub(j2_free).div_ceil(capacity);
+        best = best.max(j1 + j2 + extra as usize);
+    }
+    best
+}
@@ -0,0 +1,99 @@
+use std::time::{Duration, Instant};
+
+/// Limits on how long an improving or exact search may run. The search stops
+/// at whichever limit is reached first; with no limits set it runs until it
+/// finishes on its own.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Budget {
+    /// Maximum number of iterations (search nodes, moves tried, ...).
+    pub max_iterations: Option<u64>,
+    /// Maximum wall-clock time.
+    pub max_time: Option<Duration>,
+}
+
+impl Budget {
+    pub fn iterations(max_iterations: u64) -> Self {
+        Budget {
+            max_iterations: Some(max_iterations),
+            max_time: None,
+        }
+    }
+
+    pub fn time(max_time: Duration) -> Self {
+        Budget {
+            max_iterations: None,
+            max_time: Some(max_time),
+        }
+    }
+
+    pub fn with_max_iterations(mut self, max_iterations: u64) -> Self {
+        self.max_iterations = Some(max_iterations);
+        self
+    }
+
+    pub fn with_max_time(mut self, max_time: Duration) -> Self {
+        self.max_time = Some(max_time);
+        self
+    }
+
+    /// Whether at least one limit is set.
+    pub fn is_limited(&self) -> bool {
+        self.max_iterations.is_some() || self.max_time.is_some()
+    }
+
+    pub(crate) fn start(&self) -> Tracker {
+        Tracker {
+            budget: *self,
+            started: Instant::now(),
+            iterations: 0,
+            exhausted: false,
+        }
+    }
+}
+
+/// Counts iterations against a [`Budget`].
+#[derive(Debug)]
+pub(crate) struct Tracker {
+    budget: Budget,
+    started: Instant,
+    iterations: u64,
+    exhausted: bool,
+}
+
+impl Tracker {
+    /// Records one iteration and reports whether the search may go on.
+    pub(crate) fn tick(&mut self) -> bool {
+        if self.exhausted {
+            return false;
+        }
+        self.iterations += 1;
+        if self.budget.max_iterations.is_some_and(|max| self.iterations > max) {
+            self.exhausted = true;
+        }
+        // Reading the clock is comparatively slow, so only check it now and then.
+        if self.iterations.is_multiple_of(1024) && self.budget.max_time.is_some_and(|max| self.started.elapsed() >= max) {
+            self.exhausted = true;
+        }
+        !self.exhausted
+    }
+
+    /// Whether a limit was hit.
+    pub(crate) fn exhausted(&self) -> bool {
+        self.exhausted
+    }
+
+    pub(crate) fn iterations(&self) -> u64 {
+        self.iterations
+    }
+
+    /// Fraction of the budget used so far, in `[0, 1]`, by whichever limit
+    /// is closest to being reached.
+    pub(crate) fn progress(&self) -> f64 {
+        let by_iterations = self.budget.max_iterations.map(|max| self.iterations as f64 / max.max(1) as f64);
+        let by_time = self
+            .budget
+            .max_time
+            .map(|max| self.started.elapsed().as_secs_f64() / max.as_secs_f64().max(f64::MIN_POSITIVE));
+        by_iterations.into_iter().chain(by_time).fold(0.0, f64::max).min(1.0)
+    }
+}
@@ -0,0 +1,358 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::bin::Bin;
+use crate::error::PackError;
+use crate::item::Item;
+use crate::topology::Topology;
+
+/// A rule about which items may share a bin. Items are named by id and may
+/// be items to place or items already in existing bins.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Constraint {
+    /// At most `max_per_bin` of `items` share any one bin, e.g. to spread the
+    /// replicas of a service over several servers.
+    AntiAffinity { items: Vec<String>, max_per_bin: usize },
+    /// All of `items` go into the same bin, e.g. a service and its sidecars.
+    Affinity { items: Vec<String> },
+    /// `items` are balanced over the domains of a [`Topology`] level, e.g.
+    /// the replicas of a service over zones: the number of them in any
+    /// domain exceeds the number in any other by at most `max_skew`. Every
+    /// domain of an existing bin, or of a bin type whose `max_count` is not
+    /// zero, counts.
+    ///
+    /// If `enforced`, items only go into bins with a domain at `level`, and
+    /// not where that would take the skew over `max_skew`; items with no
+    /// such bin are left unplaced. Otherwise bins that keep the skew within
+    /// `max_skew` are preferred, when choosing among open bins and when
+    /// choosing a type for a new bin.
+    Spread {
+        items: Vec<String>,
+        level: String,
+        max_skew: usize,
+        enforced: bool,
+    },
+}
+
+impl Constraint {
+    /// No two of `items` share a bin.
+    pub fn anti_affinity<S: Into<String>>(items: impl IntoIterator<Item = S>) -> Self {
+        Constraint::at_most_per_bin(items, 1)
+    }
+
+    /// At most `max_per_bin` of `items` share a bin.
+    pub fn at_most_per_bin<S: Into<String>>(items: impl IntoIterator<Item = S>, max_per_bin: usize) -> Self {
+        Constraint::AntiAffinity {
+            items: items.into_iter().map(Into::into).collect(),
+            max_per_bin,
+        }
+    }
+
+    /// All of `items` share a bin.
+    pub fn affinity<S: Into<String>>(items: impl IntoIterator<Item = S>) -> Self {
+        Constraint::Affinity {
+            items: items.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// `items` are spread over the domains of topology level `level` with a
+    /// skew of at most `max_skew`.
+    pub fn spread<S: Into<String>>(items: impl IntoIterator<Item = S>, level: impl Into<String>, max_skew: usize) -> Self {
+        Constraint::Spread {
+            items: items.into_iter().map(Into::into).collect(),
+            level: level.into(),
+            max_skew,
+            enforced: true,
+        }
+    }
+
+    /// Like [`spread`](Constraint::spread), but only preferred.
+    pub fn prefer_spread<S: Into<String>>(items: impl IntoIterator<Item = S>, level: impl Into<String>, max_skew: usize) -> Self {
+        Constraint::Spread {
+            items: items.into_iter().map(Into::into).collect(),
+            level: level.into(),
+            max_skew,
+            enforced: false,
+        }
+    }
+
+    /// The ids of the items the constraint applies to.
+    pub fn items(&self) -> &[String] {
+        match self {
+            Constraint::AntiAffinity { items, .. } | Constraint::Affinity { items } | Constraint::Spread { items, .. } => items,
+        }
+    }
+}
+
+// A problem's constraints, indexed for checking placements. Affinity
+// constraints that share items are merged into one group. Spread
+// constraints also count their items per domain, which callers keep up to
+// date with `track`, `place` and `unplace`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ConstraintSet {
+    // Anti-affinity constraints each item is in, as indices into `limits`.
+    anti_affinity: HashMap<String, Vec<usize>>,
+    limits: Vec<usize>,
+    // Affinity group of each item that must share a bin with others.
+    group_of: HashMap<String, usize>,
+    groups: Vec<Vec<String>>,
+    // Spread constraints each item is in, as indices into `spreads`.
+    spread_of: HashMap<String, Vec<usize>>,
+    spreads: Vec<Spread>,
+    topology: Topology,
+}
+
+#[derive(Debug, Clone)]
+struct Spread {
+    level: usize,
+    max_skew: usize,
+    enforced: bool,
+    // Items of the constraint placed in each domain of the level.
+    counts: BTreeMap<String, usize>,
+}
+
+impl ConstraintSet {
+    /// Indexes `constraints` over `topology`. Fails if a constraint lists no
+    /// items, allows none per bin, spreads over a level not in `topology` or
+    /// allows no skew, or if an affinity group puts more items of an
+    /// anti-affinity constraint together than it allows.
+    pub(crate) fn new(constraints: &[Constraint], topology: &Topology) -> Result<Self, PackError> {
+        let mut set = ConstraintSet {
+            topology: topology.clone(),
+            ..ConstraintSet::default()
+        };
+        let mut ids: HashMap<&str, usize> = HashMap::new();
+        let mut parent: Vec<usize> = Vec::new();
+        for (c, constraint) in constraints.iter().enumerate() {
+            if constraint.items().is_empty() {
+                return Err(PackError::InvalidConstraint(format!("constraint {} lists no items", c)));
+            }
+            match constraint {
+                Constraint::AntiAffinity { items, max_per_bin } => {
+                    if *max_per_bin == 0 {
+                        return Err(PackError::InvalidConstraint(format!("constraint {} allows no items per bin", c)));
+                    }
+                    for id in items {
+                        let member_of = set.anti_affinity.entry(id.clone()).or_default();
+                        if member_of.last() != Some(&set.limits.len()) {
+                            member_of.push(set.limits.len());
+                        }
+                    }
+                    set.limits.push(*max_per_bin);
+                }
+                Constraint::Affinity { items } => {
+                    let mut first = None;
+                    for id in items {
+                        let next = ids.len();
+                        let x = *ids.entry(id.as_str()).or_insert_with(|| {
+                            parent.push(next);
+                            next
+                        });
+                        let x = find(&mut parent, x);
+                        let root = *first.get_or_insert(x);
+                        parent[x] = root;
+                    }
+                }
+                Constraint::Spread { items, level, max_skew, enforced } => {
+                    let Some(level) = topology.level(level) else {
+                        return Err(PackError::InvalidConstraint(format!("constraint {} spreads over {:?}, which is not a topology level", c, level)));
+                    };
+                    if *max_skew == 0 {
+                        return Err(PackError::InvalidConstraint(format!("constraint {} allows no skew", c)));
+                    }
+                    for id in items {
+                        let member_of = set.spread_of.entry(id.clone()).or_default();
+                        if member_of.last() != Some(&set.spreads.len()) {
+                            member_of.push(set.spreads.len());
+                        }
+                    }
+                    set.spreads.push(Spread {
+                        level,
+                        max_skew: *max_skew,
+                        enforced: *enforced,
+                        counts: BTreeMap::new(),
+                    });
+                }
+            }
+        }
+
+        // Items in order of first mention, grouped by their root.
+        let mut members: Vec<(&str, usize)> = ids.into_iter().collect();
+        members.sort_by_key(|&(_, x)| x);
+        let mut group_of_root: HashMap<usize, usize> = HashMap::new();
+        for (id, x) in members {
+            let root = find(&mut parent, x);
+            let next = set.groups.len();
+            let g = *group_of_root.entry(root).or_insert(next);
+            if g == next {
+                set.groups.push(Vec::new());
+            }
+            set.groups[g].push(id.to_string());
+        }
+        set.groups.retain(|group| group.len() > 1);
+        for (g, group) in set.groups.iter().enumerate() {
+            for id in group {
+                set.group_of.insert(id.clone(), g);
+            }
+        }
+
+        for group in &set.groups {
+            let mut counts = vec![0; set.limits.len()];
+            for c in group.iter().flat_map(|id| set.anti_affinity.get(id).into_iter().flatten()) {
+                counts[*c] += 1;
+            }
+            if counts.iter().zip(&set.limits).any(|(n, limit)| n > limit) {
+                return Err(PackError::Infeasible(format!(
+                    "items {} must share a bin, which an anti-affinity constraint forbids",
+                    group.join(", ")
+                )));
+            }
+        }
+        Ok(set)
+    }
+
+    /// The items that must share a bin with item `id`, itself included, if
+    /// there are any others.
+    pub(crate) fn group(&self, id: &str) -> Option<&[String]> {
+        self.group_of.get(id).map(|&g| self.groups[g].as_slice())
+    }
+
+    /// The affinity groups of more than one item.
+    pub(crate) fn groups(&self) -> &[Vec<String>] {
+        &self.groups
+    }
+
+    /// The fewest bins that can hold the items of every anti-affinity
+    /// constraint.
+    pub(crate) fn bins_needed(&self) -> usize {
+        let mut members = vec![0usize; self.limits.len()];
+        for c in self.anti_affinity.values().flatten() {
+            members[*c] += 1;
+        }
+        members.iter().zip(&self.limits).map(|(&n, &limit)| n.div_ceil(limit)).max().unwrap_or(0)
+    }
+
+    /// Whether item `id` must stay where it is when packings are improved
+    /// by moving single items: it shares a bin with others or is spread.
+    pub(crate) fn is_pinned(&self, id: &str) -> bool {
+        self.group_of.contains_key(id) || self.spread_of.contains_key(id)
+    }
+
+    /// Whether adding `incoming` to `bin` keeps every anti-affinity and
+    /// enforced spread constraint.
+    pub(crate) fn admits(&self, bin: &Bin, incoming: &[&Item]) -> bool {
+        self.keeps_anti_affinity(bin, incoming) && self.spread_breaches(bin, incoming, true) == 0
+    }
+
+    /// How many spread constraints that are only preferred adding `incoming`
+    /// to `bin` would break.
+    pub(crate) fn spread_violations(&self, bin: &Bin, incoming: &[&Item]) -> usize {
+        self.spread_breaches(bin, incoming, false)
+    }
+
+    /// How many preferred spread constraints `incoming` are in.
+    pub(crate) fn preferred_spreads(&self, incoming: &[&Item]) -> usize {
+        self.spreads_of(incoming).filter(|&(s, _)| !self.spreads[s].enforced).count()
+    }
+
+    /// Counts the items in `bins` afresh, over the domains of `bins` and of
+    /// `empty_bins`, one per bin type that can be opened.
+    pub(crate) fn track(&mut self, bins: &[Bin], empty_bins: &[Bin]) {
+        for spread in &mut self.spreads {
+            spread.counts = self.topology.domains(spread.level, bins.iter().chain(empty_bins));
+        }
+        for bin in bins {
+            self.place(bin, &bin.items().iter().collect::<Vec<_>>());
+        }
+    }
+
+    /// Counts `items` as placed in `bin`.
+    pub(crate) fn place(&mut self, bin: &Bin, items: &[&Item]) {
+        self.count(bin, items, |count| *count += 1);
+    }
+
+    /// Stops counting `items` as placed in `bin`.
+    pub(crate) fn unplace(&mut self, bin: &Bin, items: &[&Item]) {
+        self.count(bin, items, |count| *count -= 1);
+    }
+
+    fn count(&mut self, bin: &Bin, items: &[&Item], mut update: impl FnMut(&mut usize)) {
+        if self.spreads.is_empty() {
+            return;
+        }
+        let memberships: Vec<usize> = items.iter().flat_map(|item| self.spread_of.get(&item.id).into_iter().flatten().copied()).collect();
+        for s in memberships {
+            let spread = &mut self.spreads[s];
+            if let Some(domain) = self.topology.domain(bin, spread.level) {
+                update(spread.counts.entry(domain).or_default());
+            }
+        }
+    }
+
+    // The spread constraints `incoming` are in, each with how many of them
+    // it holds.
+    fn spreads_of(&self, incoming: &[&Item]) -> impl Iterator<Item = (usize, usize)> {
+        let mut spreads: Vec<(usize, usize)> = Vec::new();
+        for &s in incoming.iter().flat_map(|item| self.spread_of.get(&item.id).into_iter().flatten()) {
+            match spreads.iter_mut().find(|(t, _)| *t == s) {
+                Some((_, n)) => *n += 1,
+                None => spreads.push((s, 1)),
+            }
+        }
+        spreads.into_iter()
+    }
+
+    // How many enforced, or else preferred, spread constraints adding
+    // `incoming` to `bin` would break: the bin is outside the level's
+    // domains, or its domain would hold more than `max_skew` items over the
+    // emptiest domain.
+    fn spread_breaches(&self, bin: &Bin, incoming: &[&Item], enforced: bool) -> usize {
+        if self.spreads.is_empty() {
+            return 0;
+        }
+        self.spreads_of(incoming)
+            .filter(|&(s, _)| self.spreads[s].enforced == enforced)
+            .filter(|&(s, n)| {
+                let spread = &self.spreads[s];
+                let Some(domain) = self.topology.domain(bin, spread.level) else { return true };
+                let count = spread.counts.get(&domain).copied().unwrap_or(0);
+                let fewest = spread.counts.values().min().copied().unwrap_or(0).min(count);
+                count + n - fewest > spread.max_skew
+            })
+            .count()
+    }
+
+    fn keeps_anti_affinity(&self, bin: &Bin, incoming: &[&Item]) -> bool {
+        if self.limits.is_empty() {
+            return true;
+        }
+        // Count only the constraints the incoming items are in.
+        let mut counts: Vec<(usize, usize)> = Vec::new();
+        let count = |id: &str, counts: &mut Vec<(usize, usize)>, add: bool| {
+            for &c in self.anti_affinity.get(id).into_iter().flatten() {
+                match counts.iter_mut().find(|(d, _)| *d == c) {
+                    Some((_, n)) => *n += 1,
+                    None if add => counts.push((c, 1)),
+                    None => {}
+                }
+            }
+        };
+        for item in incoming {
+            count(&item.id, &mut counts, true);
+        }
+        if counts.is_empty() {
+            return true;
+        }
+        for item in bin.items() {
+            count(&item.id, &mut counts, false);
+        }
+        counts.iter().all(|&(c, n)| n <= self.limits[c])
+    }
+}
+
+fn find(parent: &mut [usize], mut x: usize) -> usize {
+    while parent[x] != x {
+        parent[x] = parent[parent[x]];
+        x = parent[x];
+    }
+    x
+}
@@ -0,0 +1,100 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::result::Unplaced;
+
+/// Why a packing request was rejected.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PackError {
+    /// The schema has no dimensions.
+    EmptySchema,
+    /// A demand, capacity, weight or ratio vector does not have one entry per
+    /// schema dimension.
+    DimensionMismatch { what: String, expected: usize, found: usize },
+    /// A weight is NaN, infinite or negative.
+    InvalidWeight { dimension: String, weight: f32 },
+    /// All weights are zero, so they cannot be normalised.
+    ZeroWeights,
+    /// An overcommit ratio is NaN, infinite, zero or negative.
+    InvalidOvercommit { dimension: String, ratio: f32 },
+    /// The problem has no bin types to open.
+    EmptyCatalogue,
+    /// A bin type has no capacity in some dimension.
+    ZeroCapacity { bin_type: String, dimension: String },
+    /// An existing bin refers to a bin type that is not in the catalogue.
+    UnknownBinType { bin: usize, bin_type: usize },
+    /// An existing bin has no capacity in some dimension.
+    ZeroBinCapacity { bin: usize, dimension: String },
+    /// A bin type's cost is NaN, infinite or negative.
+    InvalidCost { bin_type: String, cost: f64 },
+    /// An item cannot be placed and
+    /// [`PackOptions::fail_on_oversized`](crate::PackOptions::fail_on_oversized)
+    /// is set, or an item inserted into a [`Packer`](crate::Packer) fits no
+    /// open bin and no bin type.
+    OversizedItem(Box<Unplaced>),
+    /// The item with this id, inserted into a [`Packer`](crate::Packer), fits
+    /// no open bin and no more bins may be opened.
+    NoBinAvailable(String),
+    /// There are no items to pack.
+    EmptyInput,
+    /// Two items share the same id.
+    DuplicateItemId(String),
+    /// The solver cannot handle this kind of problem or option.
+    Unsupported(String),
+    /// A solution read back from an external solver is not a valid packing.
+    InvalidSolution(String),
+    /// A [`Constraint`](crate::Constraint) lists no items, allows none per
+    /// bin, or names an item that is not in the problem.
+    InvalidConstraint(String),
+    /// The constraints cannot all be met within the capacities and bin
+    /// limits.
+    Infeasible(String),
+    /// A [`PlacementStrategy`](crate::PlacementStrategy) selected a bin that
+    /// does not exist, that the item does not fit in, or that the
+    /// constraints do not let it into.
+    InvalidStrategy { item: String, bin: usize },
+}
+
+impl fmt::Display for PackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PackError::EmptySchema => f.write_str("resource schema has no dimensions"),
+            PackError::DimensionMismatch { what, expected, found } => {
+                write!(f, "{} has {} dimensions, schema has {}", what, found, expected)
+            }
+            PackError::InvalidWeight { dimension, weight } => {
+                write!(f, "{} weight {} must be finite and non-negative", dimension, weight)
+            }
+            PackError::ZeroWeights => f.write_str("at least one weight must be positive"),
+            PackError::InvalidOvercommit { dimension, ratio } => {
+                write!(f, "{} overcommit ratio {} must be finite and positive", dimension, ratio)
+            }
+            PackError::EmptyCatalogue => f.write_str("no bin types to open"),
+            PackError::ZeroCapacity { bin_type, dimension } => {
+                write!(f, "bin type {} {} capacity must be positive", bin_type, dimension)
+            }
+            PackError::UnknownBinType { bin, bin_type } => {
+                write!(f, "existing bin {} has unknown bin type {}", bin, bin_type)
+            }
+            PackError::ZeroBinCapacity { bin, dimension } => {
+                write!(f, "existing bin {} {} capacity must be positive", bin, dimension)
+            }
+            PackError::InvalidCost { bin_type, cost } => {
+                write!(f, "bin type {} cost {} must be finite and non-negative", bin_type, cost)
+            }
+            PackError::OversizedItem(unplaced) => unplaced.fmt(f),
+            PackError::NoBinAvailable(id) => write!(f, "item {} fits no open bin and no more bins may be opened", id),
+            PackError::EmptyInput => f.write_str("no items to pack"),
+            PackError::DuplicateItemId(id) => write!(f, "item id {:?} is used more than once", id),
+            PackError::Unsupported(reason) => f.write_str(reason),
+            PackError::InvalidSolution(reason) => write!(f, "invalid solution: {}", reason),
+            PackError::InvalidConstraint(reason) => write!(f, "invalid constraint: {}", reason),
+            PackError::Infeasible(reason) => write!(f, "infeasible: {}", reason),
+            PackError::InvalidStrategy { item, bin } => {
+                write!(f, "strategy selected bin {} for item {}, which it does not fit in or may not go into", bin, item)
+            }
+        }
+    }
+}
+
+impl Error for PackError {}
@@ -0,0 +1,237 @@
+use crate::bin::Bin;
+use crate::bounds;
+use crate::budget::{Budget, Tracker};
+use crate::error::PackError;
+use crate::ffd::pack;
+use crate::item::Item;
+use crate::options::{CapacityMode, PackOptions};
+use crate::problem::Problem;
+use crate::result::PackResult;
+
+/// Outcome of [`solve_exact`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExactSolution {
+    pub result: PackResult,
+    /// Whether `result` is proven to use as few bins as possible.
+    pub optimal: bool,
+    /// Number of search nodes explored.
+    pub nodes: u64,
+}
+
+/// Finds a packing with the fewest bins by branch-and-bound.
+///
+/// The search starts from the packing [`pack`] finds with `options` as the
+/// incumbent, assigns items in `options.ordering` to each open bin they fit
+/// in or to one new bin, and prunes any node whose L1 or L2 bound on the
+/// remaining demand cannot beat the incumbent. It stops when the incumbent
+/// matches the L1/L2 [`lower_bound`](PackResult::lower_bound), the tree is
+/// exhausted, or `budget` runs out; `budget` counts search nodes as
+/// iterations. Only in the first two cases is the result flagged
+/// [`optimal`](ExactSolution::optimal).
+///
+/// The problem must have a single bin type without a `max_count`, no
+/// existing bins, and `options` must use [`CapacityMode::Strict`]. Items too
+/// large for the bin type are reported as unplaced, as with [`pack`].
+///
+/// ```
+/// use bin_packer::{pack, solve_exact, Budget, Item, PackOptions, Problem, ResourceSchema};
+///
+/// let demands = [[8, 8], [2, 48], [3, 24], [1, 48], [1, 24]];
+/// let items = demands.into_iter().enumerate().map(|(i, demand)| Item::new(format!("job-{}", i), demand));
+/// let problem = Problem::new(ResourceSchema::new(["cores", "memory_gb"]), [10, 80]).with_items(items);
+/// let options = PackOptions::new([0.5, 0.5]);
+/// assert_eq!(pack(&problem, &options)?.bins_used(), 3);
+///
+/// let solution = solve_exact(&problem, &options, &Budget::iterations(10_000))?;
+/// assert_eq!(solution.result.bins_used(), 2);
+/// assert!(solution.optimal);
+///
+/// // A single search node neither finds nor proves a better packing.
+/// let cut_short = solve_exact(&problem, &options, &Budget::iterations(1))?;
+/// assert_eq!(cut_short.result.bins_used(), 3);
+/// assert!(!cut_short.optimal);
+///
+/// // Three bins are needed but the bound only says two, so optimality is
+/// // proven by searching the whole tree, which pruning keeps to a few nodes.
+/// let items = [[5, 64], [4, 24], [6, 16], [5, 32]].into_iter().enumerate().map(|(i, demand)| Item::new(format!("job-{}", i), demand));
+/// let problem = Problem::new(ResourceSchema::new(["cores", "memory_gb"]), [10, 80]).with_items(items);
+/// let solution = solve_exact(&problem, &options, &Budget::iterations(10_000))?;
+/// assert_eq!((solution.result.bins_used(), solution.result.lower_bound), (3, Some(2)));
+/// assert!(solution.optimal && solution.nodes < 10);
+/// # Ok::<(), bin_packer::PackError>(())
+/// ```
+pub fn solve_exact(problem: &Problem, options: &PackOptions, budget: &Budget) -> Result<ExactSolution, PackError> {
+    problem.validate()?;
+    let options = &options.normalized(&problem.schema)?;
+    check_supported(problem, options)?;
+
+    let incumbent = pack(problem, options)?;
+    let bin_type = &problem.bin_types[0];
+    let empty_bin = Bin::of_type(0, bin_type);
+    let limits: Vec<u64> = (0..problem.schema.len()).map(|d| empty_bin.limit(d, options)).collect();
+
+    let items: Vec<&Item> = options
+        .ordering
+        .sort(&problem.items, problem, &options.weights, options.tie_break_seed)
+        .into_iter()
+        .filter(|item| empty_bin.fits(item, options))
+        .collect();
+    let demands: Vec<Vec<u64>> = items.iter().map(|item| item.demand.iter().map(|&q| q as u64).collect()).collect();
+
+    let lower_bound = incumbent.lower_bound.expect("strict capacity mode");
+    let mut search = Search::new(&demands, &limits, incumbent.bins.len(), lower_bound, budget.start());
+    search.run(0);
+    let optimal = search.best == lower_bound || !search.tracker.exhausted();
+    let nodes = search.tracker.iterations();
+
+    let result = match search.best_assignment {
+        Some(assignment) => {
+            let mut bins = vec![empty_bin; search.best];
+            for (item, b) in items.into_iter().zip(assignment) {
+                bins[b].push_item(item.clone());
+            }
+            PackResult {
+                cost: bins.len() as f64 * bin_type.cost,
+                bins,
+                existing_bins: 0,
+                unplaced: incumbent.unplaced,
+                lower_bound: Some(lower_bound),
+            }
+        }
+        None => incumbent,
+    };
+    Ok(ExactSolution { result, optimal, nodes })
+}
+
+fn check_supported(problem: &Problem, options: &PackOptions) -> Result<(), PackError> {
+    let reason = if problem.bin_types.len() != 1 {
+        "the exact solver needs exactly one bin type"
+    } else if problem.bin_types[0].max_count.is_some() {
+        "the exact solver does not support a bin type max_count"
+    } else if !problem.existing_bins.is_empty() {
+        "the exact solver does not support existing bins"
+    } else if matches!(options.capacity_mode, CapacityMode::Weighted) {
+        "the exact solver needs the strict capacity mode"
+    } else if !problem.constraints.is_empty() {
+        "the exact solver does not support item constraints"
+    } else {
+        return Ok(());
+    };
+    Err(PackError::Unsupported(reason.to_string()))
+}
+
+struct Search<'a> {
+    demands: &'a [Vec<u64>],
+    limits: &'a [u64],
+    // remaining[k][d] is the total demand of items k.. in dimension d.
+    remaining: Vec<Vec<u64>>,
+    loads: Vec<Vec<u64>>,
+    assignment: Vec<usize>,
+    best: usize,
+    best_assignment: Option<Vec<usize>>,
+    lower_bound: usize,
+    tracker: Tracker,
+}
+
+impl<'a> Search<'a> {
+    fn new(demands: &'a [Vec<u64>], limits: &'a [u64], best: usize, lower_bound: usize, tracker: Tracker) -> Self {
+        let mut remaining = vec![vec![0; limits.len()]; demands.len() + 1];
+        for k in (0..demands.len()).rev() {
+            for d in 0..limits.len() {
+                remaining[k][d] = remaining[k + 1][d] + demands[k][d];
+            }
+        }
+        Search {
+            demands,
+            limits,
+            remaining,
+            loads: Vec::new(),
+            assignment: Vec::with_capacity(demands.len()),
+            best,
+            best_assignment: None,
+            lower_bound,
+            tracker,
+        }
+    }
+
+    fn run(&mut self, k: usize) {
+        if self.best <= self.lower_bound || !self.tracker.tick() {
+            return;
+        }
+        if k == self.demands.len() {
+            if self.loads.len() < self.best {
+                self.best = self.loads.len();
+                self.best_assignment = Some(self.assignment.clone());
+            }
+            return;
+        }
+        if self.bound(k) >= self.best {
+            return;
+        }
+
+        // Try the open bins tightest first, skipping bins whose load equals
+        // one already tried since they lead to the same subtrees.
+        let demand = &self.demands[k];
+        let mut candidates: Vec<(f64, usize)> = (0..self.loads.len())
+            .filter(|&b| (0..self.limits.len()).all(|d| self.loads[b][d] + demand[d] <= self.limits[d]))
+            .map(|b| (self.slack(b, demand), b))
+            .collect();
+        candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut tried: Vec<usize> = Vec::new();
+        for (_, b) in candidates {
+            if tried.iter().any(|&t| self.loads[t] == self.loads[b]) {
+                continue;
+            }
+            tried.push(b);
+            self.place(k, b);
+            self.run(k + 1);
+            self.unplace(k, b);
+        }
+
+        // All new bins are alike, so a single branch covers opening one.
+        if self.loads.len() + 1 < self.best {
+            self.loads.push(vec![0; self.limits.len()]);
+            let b = self.loads.len() - 1;
+            self.place(k, b);
+            self.run(k + 1);
+            self.unplace(k, b);
+            self.loads.pop();
+        }
+    }
+
+    // The larger of two bounds in the tightest dimension: the open bins plus
+    // the bins the remaining demand needs beyond the free space in the open
+    // ones, and L2 of the residual instance in which the load of each open
+    // bin is a single item. Any completion of this node packs that instance.
+    fn bound(&self, k: usize) -> usize {
+        (0..self.limits.len())
+            .map(|d| {
+                let free: u64 = self.loads.iter().map(|load| self.limits[d] - load[d]).sum();
+                let extra = self.remaining[k][d].saturating_sub(free).div_ceil(self.limits[d]) as usize;
+                let sizes: Vec<u64> = self.loads.iter().map(|load| load[d]).chain(self.demands[k..].iter().map(|demand| demand[d])).collect();
+                (self.loads.len() + extra).max(bounds::l2(&sizes, self.limits[d]))
+            })
+            .max()
+            .unwrap_or(self.loads.len())
+    }
+
+    fn slack(&self, b: usize, demand: &[u64]) -> f64 {
+        (0..self.limits.len())
+            .map(|d| (self.limits[d] - self.loads[b][d] - demand[d]) as f64 / self.limits[d] as f64)
+            .sum()
+    }
+
+    fn place(&mut self, k: usize, b: usize) {
+        for (load, demand) in self.loads[b].iter_mut().zip(&self.demands[k]) {
+            *load += demand;
+        }
+        self.assignment.push(b);
+    }
+
+    fn unplace(&mut self, k: usize, b: usize) {
+        for (load, demand) in self.loads[b].iter_mut().zip(&self.demands[k]) {
+            *load -= demand;
+        }
+        self.assignment.pop();
+    }
+}
@@ -0,0 +1,413 @@
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::bin::Bin;
+use crate::bounds;
+use crate::constraint::ConstraintSet;
+use crate::error::PackError;
+use crate::index::BinIndex;
+use crate::item::Item;
+use crate::local_search;
+use crate::options::PackOptions;
+use crate::ordering::{self, ItemOrdering};
+use crate::problem::Problem;
+use crate::resource::ResourceSchema;
+use crate::result::{PackResult, Unplaced, UnplacedReason};
+use crate::strategy::PlacementContext;
+
+// How many upcoming items are tried in a candidate bin when choosing which
+// bin type to open.
+const LOOKAHEAD: usize = 256;
+
+/// Packs two-dimensional `items`, with `[cores, disk]` demands, into bins
+/// of `core_capacity` cores and `disk_capacity` disk using weighted
+/// First-Fit Decreasing with the legacy
+/// [`CapacityMode::Weighted`](crate::CapacityMode::Weighted) fit check.
+///
+/// Items are sorted by `cores * core_weight + disk * disk_weight` on raw
+/// units, largest first, and each one goes into the first bin it fits in. Use
+/// [`pack`] with [`PackOptions::new`] for packing that never overcommits a
+/// bin and compares dimensions on a common scale. As that fit check can
+/// fill a bin past its capacity, the result's lower bound is 0.
+pub fn bin_packing_weighted_ffd(
+    items: &[Item],
+    core_capacity: u32,
+    disk_capacity: u32,
+    core_weight: f32,
+    disk_weight: f32,
+) -> Result<PackResult, PackError> {
+    let problem = Problem::new(ResourceSchema::new(["cores", "disk"]), [core_capacity, disk_capacity])
+        .with_items(items.iter().cloned());
+    let raw_weighted_size = move |item: &Item, _: &[f64]| {
+        item.demand[0] as f64 * core_weight as f64 + item.demand[1] as f64 * disk_weight as f64
+    };
+    let options = PackOptions::weighted([core_weight, disk_weight]).with_ordering(ItemOrdering::custom(raw_weighted_size));
+    pack(&problem, &options)
+}
+
+/// Packs the items of `problem` in decreasing size order.
+///
+/// Items are sorted by `options.ordering`, by default the weighted sum of
+/// their demands as fractions of the largest bin, largest first. Each one
+/// goes into the bin chosen by
+/// `options.strategy` among those that accept it under
+/// `options.capacity_mode`, with the problem's existing bins listed before
+/// the ones opened so far. The default [`FirstFit`](crate::FirstFit) strategy
+/// gives weighted First-Fit Decreasing. When no bin is chosen, a bin of the
+/// type with the lowest cost per unit of weighted size is opened, judged by
+/// how well the upcoming items would fill it. Finally every bin is switched
+/// to the cheapest type that still holds its items. Existing bins keep their
+/// type, and `max_count` only limits the bins opened here.
+///
+/// If `options.local_search` is set, the packing is then improved with
+/// [`improve`](crate::improve).
+///
+/// The problem's [`Constraint`](crate::Constraint)s are kept whatever the
+/// strategy. Items that must share a bin are placed together, as one item
+/// with their total demand; if one of them is already in an existing bin,
+/// the others join it there before anything else is placed.
+///
+/// Items only go into bins carrying the labels they require. An item that no
+/// bin type carries those labels for can still go into an existing bin that
+/// does, and is otherwise reported as unplaced.
+///
+/// Items that cannot be placed are reported in [`PackResult::unplaced`]. The
+/// result also carries a lower bound on the bins needed for the placed items.
+///
+/// Fails if the problem is empty or inconsistent with its schema, a capacity
+/// is zero, a cost is invalid, or the weights or overcommit ratios in
+/// `options` are invalid. Fails with [`PackError::Infeasible`] if items that
+/// must share a bin fit in no bin together or require different values of
+/// a label, or anti-affinity constraints need more bins than may be used.
+/// Fails with [`PackError::InvalidStrategy`] if `options.strategy` selects a
+/// bin the item does not fit in or the constraints keep it out of.
+pub fn pack(problem: &Problem, options: &PackOptions) -> Result<PackResult, PackError> {
+    problem.validate()?;
+    let options = &options.normalized(&problem.schema)?;
+    let mut constraints = ConstraintSet::new(&problem.constraints, &problem.topology)?;
+    check_bin_limits(problem, &constraints)?;
+
+    let empty_bins: Vec<Bin> = problem.bin_types.iter().enumerate().map(|(t, bt)| Bin::of_type(t, bt)).collect();
+    let mut opened = vec![0; empty_bins.len()];
+    let mut bins: Vec<Bin> = problem.existing_bins.clone();
+    let existing_bins = bins.len();
+    constraints.track(&bins, &problem.openable_bins());
+
+    let (units, members_of) = affinity_units(problem, &mut constraints, &mut bins, options)?;
+    let reference = problem.reference_capacity();
+    let sorted_items = options.ordering.sort(&units, problem, &options.weights, options.tie_break_seed);
+    let sizes: Vec<f64> = sorted_items.iter().map(|item| weighted_size(item, &reference, options)).collect();
+    let dimensions = problem.schema.len();
+    let mut index = BinIndex::new(&bins, dimensions, options);
+    // Least demand per dimension among the items from each position on, so
+    // the index can drop bins no later item fits in.
+    let mut floors = vec![u32::MAX; (sorted_items.len() + 1) * dimensions];
+    for i in (0..sorted_items.len()).rev() {
+        for d in 0..dimensions {
+            floors[i * dimensions + d] = floors[(i + 1) * dimensions + d].min(sorted_items[i].demand[d]);
+        }
+    }
+    let mut unplaced = Vec::new();
+    let mut current = None;
+
+    for (i, &item) in sorted_items.iter().enumerate() {
+        let members = members_of.get(item.id.as_str()).map_or(std::slice::from_ref(&item), Vec::as_slice);
+        index.raise_floor(&floors[i * dimensions..(i + 1) * dimensions]);
+        let context = PlacementContext::new(&bins, &index, item, members, &constraints, options, current);
+        let selected = context.select(options.strategy.as_ref())?;
+        if let Some(b) = selected {
+            for &member in members {
+                bins[b].push_item(member.clone());
+            }
+            index.update(&bins, b, options);
+            constraints.place(&bins[b], members);
+            current = Some(b);
+        } else {
+            let fitting: Vec<usize> = (0..empty_bins.len()).filter(|&t| empty_bins[t].fits(item, options)).collect();
+            if fitting.is_empty() && members.len() > 1 {
+                return Err(PackError::Infeasible(format!("items {} must share a bin but fit in no bin type together", ids(members))));
+            }
+            if fitting.is_empty() {
+                let reason = oversized(&empty_bins, item, problem, options);
+                let item = Unplaced { item: item.clone(), reason };
+                if options.fail_on_oversized {
+                    return Err(PackError::OversizedItem(Box::new(item)));
+                }
+                unplaced.push(item);
+                continue;
+            }
+
+            // Create a new bin if the strategy did not pick an existing one,
+            // of a type whose domains the enforced spread constraints allow,
+            // and the preferred ones too if any available type keeps them.
+            let admitted: Vec<usize> = fitting.into_iter().filter(|&t| constraints.admits(&empty_bins[t], members)).collect();
+            if admitted.is_empty() {
+                unplaced.extend(members.iter().map(|&member| Unplaced {
+                    item: member.clone(),
+                    reason: UnplacedReason::SpreadLimit,
+                }));
+                continue;
+            }
+            let available: Vec<usize> = admitted.into_iter().filter(|&t| has_capacity_left(problem, &opened, t)).collect();
+            let keeping: Vec<usize> = available.iter().copied().filter(|&t| constraints.spread_violations(&empty_bins[t], members) == 0).collect();
+            let candidates = if keeping.is_empty() { available } else { keeping };
+            let end = sorted_items.len().min(i + LOOKAHEAD);
+            match choose_bin_type(candidates.into_iter(), &empty_bins, &sorted_items[i..end], &sizes[i..end], problem, options) {
+                Some(t) => {
+                    let mut new_bin = empty_bins[t].clone();
+                    for &member in members {
+                        new_bin.push_item(member.clone());
+                    }
+                    opened[t] += 1;
+                    bins.push(new_bin);
+                    index.update(&bins, bins.len() - 1, options);
+                    constraints.place(&bins[bins.len() - 1], members);
+                    current = Some(bins.len() - 1);
+                }
+                None => unplaced.extend(members.iter().map(|&member| Unplaced {
+                    item: member.clone(),
+                    reason: UnplacedReason::NoBinAvailable,
+                })),
+            }
+        }
+    }
+
+    downsize(&mut bins[existing_bins..], problem, options);
+    let cost = total_cost(&bins[existing_bins..], problem);
+    let unplaced_ids: HashSet<&str> = unplaced.iter().map(|u| u.item.id.as_str()).collect();
+    let placed: Vec<&Item> = problem.items.iter().filter(|item| !unplaced_ids.contains(item.id.as_str())).collect();
+    let lower_bound = bounds::for_items(&placed, problem, options).map(|bounds| bounds.best());
+    let result = PackResult {
+        bins,
+        existing_bins,
+        unplaced,
+        cost,
+        lower_bound,
+    };
+    Ok(match &options.local_search {
+        Some(budget) => local_search::run(problem, result, options, &constraints, budget),
+        None => result,
+    })
+}
+
+// Fails if the anti-affinity constraints need more bins than the existing
+// bins and the bin types' `max_count` allow.
+fn check_bin_limits(problem: &Problem, constraints: &ConstraintSet) -> Result<(), PackError> {
+    let limits: Option<usize> = problem.bin_types.iter().map(|bin_type| bin_type.max_count).sum();
+    let needed = constraints.bins_needed();
+    match limits {
+        Some(limit) if needed > problem.existing_bins.len() + limit => Err(PackError::Infeasible(format!(
+            "anti-affinity constraints need at least {} bins but at most {} may be used",
+            needed,
+            problem.existing_bins.len() + limit
+        ))),
+        _ => Ok(()),
+    }
+}
+
+// The units to place: the problem's items, except that each affinity group
+// becomes one item with the group's total demand and the id of its first
+// member, listed where that member is. Also returns the members each such
+// unit stands for, by id. Groups with an item in an existing bin are placed
+// in that bin instead.
+#[allow(clippy::type_complexity)]
+fn affinity_units<'a>(
+    problem: &'a Problem,
+    constraints: &mut ConstraintSet,
+    bins: &mut [Bin],
+    options: &PackOptions,
+) -> Result<(Cow<'a, [Item]>, HashMap<String, Vec<&'a Item>>), PackError> {
+    if constraints.groups().is_empty() {
+        return Ok((Cow::Borrowed(&problem.items), HashMap::new()));
+    }
+    let by_id: HashMap<&str, &Item> = problem.items.iter().map(|item| (item.id.as_str(), item)).collect();
+    let mut members_of: HashMap<String, Vec<&Item>> = HashMap::new();
+    let mut grouped: HashSet<&str> = HashSet::new();
+    for group in constraints.groups().to_vec() {
+        let hosts: BTreeSet<usize> = (0..bins.len()).filter(|&b| bins[b].items().iter().any(|item| group.contains(&item.id))).collect();
+        if hosts.len() > 1 {
+            return Err(PackError::Infeasible(format!(
+                "items {} must share a bin but are already in existing bins {:?}",
+                group.join(", "),
+                hosts
+            )));
+        }
+        let members: Vec<&Item> = group.iter().filter_map(|id| by_id.get(id.as_str()).copied()).collect();
+        let Some(first) = members.first() else { continue };
+        grouped.extend(members.iter().map(|item| item.id.as_str()));
+        let unit = combined(first.id.clone(), &members, problem.schema.len())?;
+        match hosts.first() {
+            None => {
+                members_of.insert(unit.id, members);
+            }
+            Some(&b) => {
+                if !bins[b].fits(&unit, options) || !constraints.admits(&bins[b], &members) {
+                    return Err(PackError::Infeasible(format!(
+                        "items {} must join existing bin {}, which cannot take them",
+                        ids(&members),
+                        b
+                    )));
+                }
+                for &member in &members {
+                    bins[b].push_item(member.clone());
+                }
+                constraints.place(&bins[b], &members);
+            }
+        }
+    }
+
+    let mut units = Vec::with_capacity(problem.items.len());
+    for item in &problem.items {
+        if let Some(members) = members_of.get(&item.id) {
+            units.push(combined(item.id.clone(), members, problem.schema.len())?);
+        } else if !grouped.contains(item.id.as_str()) {
+            units.push(item.clone());
+        }
+    }
+    Ok((Cow::Owned(units), members_of))
+}
+
+// An item standing for `members`, with their total demand and all their
+// required and preferred labels. Fails if two members require different
+// values of a label.
+fn combined(id: String, members: &[&Item], dimensions: usize) -> Result<Item, PackError> {
+    let mut unit = Item::new(id, vec![0u32; dimensions]);
+    for member in members {
+        for (total, &q) in unit.demand.iter_mut().zip(&member.demand) {
+            *total = total.saturating_add(q);
+        }
+        for (key, value) in &member.required_labels {
+            if unit.required_labels.get(key).is_some_and(|required| required != value) {
+                return Err(PackError::Infeasible(format!(
+                    "items {} must share a bin but require different values of label {}",
+                    ids(members),
+                    key
+                )));
+            }
+            unit.required_labels.insert(key.clone(), value.clone());
+        }
+        for (key, value) in &member.preferred_labels {
+            unit.preferred_labels.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+    }
+    Ok(unit)
+}
+
+fn ids(items: &[&Item]) -> String {
+    items.iter().map(|item| item.id.as_str()).collect::<Vec<_>>().join(", ")
+}
+
+/// Weighted sum of `item`'s demands as fractions of `reference`.
+pub(crate) fn weighted_size(item: &Item, reference: &[u32], options: &PackOptions) -> f64 {
+    ordering::weighted_sum(&ordering::normalize(item, reference), &options.weights)
+}
+
+/// Cost of `bins` that were opened from the catalogue.
+pub(crate) fn total_cost(bins: &[Bin], problem: &Problem) -> f64 {
+    bins.iter().filter_map(|bin| bin.bin_type).map(|t| problem.bin_types[t].cost).sum()
+}
+
+pub(crate) fn has_capacity_left(problem: &Problem, opened: &[usize], t: usize) -> bool {
+    problem.bin_types[t].max_count.is_none_or(|max| opened[t] < max)
+}
+
+// Why `item` fits no bin type: the oversize against the type, among those
+// carrying the labels it requires, that it misses in the fewest dimensions,
+// or that no type carries them.
+pub(crate) fn oversized(empty_bins: &[Bin], item: &Item, problem: &Problem, options: &PackOptions) -> UnplacedReason {
+    empty_bins
+        .iter()
+        .filter(|bin| bin.has_labels(&item.required_labels))
+        .map(|bin| (bin, bin.exceeded(item, &problem.schema, options)))
+        .min_by_key(|(_, exceeded)| exceeded.len())
+        .map(|(bin, exceeded)| UnplacedReason::Oversized {
+            bin_type: problem.bin_types[bin.bin_type.unwrap_or(0)].name.clone(),
+            exceeded,
+        })
+        .unwrap_or(UnplacedReason::NoMatchingBinType)
+}
+
+// Picks the candidate type with the lowest cost per unit of weighted size when
+// first-fitting `upcoming` (starting with the item being placed), whose
+// weighted sizes are `sizes`, into an empty bin of that type. Ties go to the
+// cheaper type.
+pub(crate) fn choose_bin_type(
+    candidates: impl Iterator<Item = usize>,
+    empty_bins: &[Bin],
+    upcoming: &[&Item],
+    sizes: &[f64],
+    problem: &Problem,
+    options: &PackOptions,
+) -> Option<usize> {
+    let candidates: Vec<usize> = candidates.collect();
+    if candidates.len() <= 1 {
+        return candidates.first().copied();
+    }
+
+    let mut best: Option<(f64, f64, usize)> = None;
+    for t in candidates {
+        // The trial bin takes the items' demands as reserved usage rather
+        // than copies of the items.
+        let mut bin = empty_bins[t].clone();
+        bin.reserved = vec![0; bin.capacity.len()];
+        let mut packed = 0.0;
+        for (&item, &size) in upcoming.iter().zip(sizes) {
+            if bin.fits(item, options) {
+                packed += size;
+                for (reserved, &demand) in bin.reserved.iter_mut().zip(&item.demand) {
+                    *reserved = reserved.saturating_add(demand);
+                }
+            }
+        }
+        let cost = problem.bin_types[t].cost;
+        let score = if packed > 0.0 { cost / packed } else { cost };
+        if best.is_none_or(|(best_score, best_cost, _)| (score, cost) < (best_score, best_cost)) {
+            best = Some((score, cost, t));
+        }
+    }
+    best.map(|(_, _, t)| t)
+}
+
+/// Drops the empty bins among `bins` after the first `existing_bins`, which
+/// were opened by a solver. Existing bins are kept even when empty.
+pub(crate) fn drop_empty_new_bins(bins: &mut Vec<Bin>, existing_bins: usize) {
+    let mut index = 0;
+    bins.retain(|bin| {
+        index += 1;
+        index <= existing_bins || !bin.items().is_empty()
+    });
+}
+
+/// Moves each of `bins`, all opened from the catalogue, to the cheapest type
+/// that still holds its items without exceeding any type's `max_count`.
+/// Only types with the same labels are considered, so that the labels items
+/// chose the bin for and its topology domains stay the same.
+pub(crate) fn downsize(bins: &mut [Bin], problem: &Problem, options: &PackOptions) {
+    let empty_bins: Vec<Bin> = problem.bin_types.iter().enumerate().map(|(t, bt)| Bin::of_type(t, bt)).collect();
+    let mut opened = vec![0; empty_bins.len()];
+    for t in bins.iter().filter_map(|bin| bin.bin_type) {
+        opened[t] += 1;
+    }
+    for bin in bins.iter_mut() {
+        let Some(current) = bin.bin_type else { continue };
+        let mut best = current;
+        for (t, empty_bin) in empty_bins.iter().enumerate() {
+            if problem.bin_types[t].cost >= problem.bin_types[best].cost
+                || problem.bin_types[t].labels != bin.labels
+                || !has_capacity_left(problem, &opened, t)
+            {
+                continue;
+            }
+            let mut candidate = empty_bin.clone();
+            if bin.items().iter().all(|item| candidate.add_item(item.clone(), options)) {
+                best = t;
+            }
+        }
+        if best != current {
+            opened[current] -= 1;
+            opened[best] += 1;
+            bin.bin_type = Some(best);
+            bin.capacity = empty_bins[best].capacity.clone();
+        }
+    }
+}
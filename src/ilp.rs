@@ -0,0 +1,337 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use crate::bin::Bin;
+use crate::error::PackError;
+use crate::ffd::{self, pack};
+use crate::item::Item;
+use crate::options::{CapacityMode, PackOptions};
+use crate::problem::Problem;
+use crate::result::{PackResult, Unplaced};
+
+// Terms per line when writing long rows, to stay well within the line
+// length limits of LP readers.
+const TERMS_PER_LINE: usize = 8;
+
+/// The standard assignment ILP for a packing instance, ready to be written
+/// out for an external MIP solver.
+///
+/// Each placeable item `i` and candidate bin `j` get a binary `x_i_j` that is
+/// 1 when the item goes into the bin, and each candidate new bin a binary
+/// `y_j` that is 1 when it is opened:
+///
+/// ```text
+/// minimise    sum_j cost_j * y_j
+/// subject to  sum_j x_i_j = 1                            for each item i
+///             sum_i demand_i_d * x_i_j <= cap_j_d * y_j  for each new bin j, dimension d
+///             sum_i demand_i_d * x_i_j <= free_j_d       for each existing bin j, dimension d
+///             y_j >= y_j+1                               for consecutive bins of one type
+/// ```
+///
+/// Existing bins come first among the candidates, followed for each bin
+/// type by as many new bins as any packing could use: the number of items
+/// that fit it, or its `max_count` if lower. Items too large for every bin,
+/// which [`pack`] reports as unplaced, are left out of the model.
+#[derive(Debug, Clone)]
+pub struct IlpModel<'a> {
+    problem: &'a Problem,
+    options: PackOptions,
+    // Indices into problem.items of the items in the model.
+    items: Vec<usize>,
+    // Candidate bins, each empty, existing bins first.
+    bins: Vec<Bin>,
+    // Costs per candidate bin; 0 for existing bins.
+    costs: Vec<f64>,
+    // Items left out because they fit no bin, as reported by `pack`.
+    unplaced: Vec<Unplaced>,
+}
+
+impl<'a> IlpModel<'a> {
+    /// Builds the model for `problem` with capacities and overcommit ratios
+    /// from `options`, which must use [`CapacityMode::Strict`]. The problem
+    /// must have no constraints and no item may require labels.
+    pub fn new(problem: &'a Problem, options: &PackOptions) -> Result<Self, PackError> {
+        problem.validate()?;
+        let options = options.normalized(&problem.schema)?;
+        if matches!(options.capacity_mode, CapacityMode::Weighted) {
+            return Err(PackError::Unsupported("the ILP model needs the strict capacity mode".to_string()));
+        }
+        if !problem.constraints.is_empty() {
+            return Err(PackError::Unsupported("the ILP model does not support item constraints".to_string()));
+        }
+        if problem.has_required_labels() {
+            return Err(PackError::Unsupported("the ILP model does not support required labels".to_string()));
+        }
+
+        let heuristic = pack(problem, &options)?;
+        let empty_bins: Vec<Bin> = problem.bin_types.iter().enumerate().map(|(t, bt)| Bin::of_type(t, bt)).collect();
+        let items: Vec<usize> = (0..problem.items.len())
+            .filter(|&i| empty_bins.iter().any(|bin| bin.fits(&problem.items[i], &options)))
+            .collect();
+
+        let mut bins: Vec<Bin> = problem.existing_bins.clone();
+        let mut costs = vec![0.0; bins.len()];
+        for (t, bin_type) in problem.bin_types.iter().enumerate() {
+            let fitting = items.iter().filter(|&&i| empty_bins[t].fits(&problem.items[i], &options)).count();
+            let count = bin_type.max_count.map_or(fitting, |max_count| fitting.min(max_count));
+            bins.extend(std::iter::repeat_n(empty_bins[t].clone(), count));
+            costs.extend(std::iter::repeat_n(bin_type.cost, count));
+        }
+
+        let unplaced = heuristic
+            .unplaced
+            .into_iter()
+            .filter(|u| !empty_bins.iter().any(|bin| bin.fits(&u.item, &options)))
+            .collect();
+        Ok(IlpModel { problem, options, items, bins, costs, unplaced })
+    }
+
+    /// Number of binary variables.
+    pub fn variables(&self) -> usize {
+        self.items.len() * self.bins.len() + self.bins.len() - self.problem.existing_bins.len()
+    }
+
+    /// Writes the model in CPLEX LP format.
+    pub fn write_lp(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "\\ Bin packing assignment model")?;
+        for (i, &item) in self.items.iter().enumerate() {
+            writeln!(out, "\\ item {} = {}", i, self.problem.items[item].id)?;
+        }
+        writeln!(out, "Minimize")?;
+        let objective: Vec<(f64, String)> = self.new_bins().map(|j| (self.costs[j], y(j))).collect();
+        write_row(out, " obj:", &objective, "")?;
+
+        writeln!(out, "Subject To")?;
+        for i in 0..self.items.len() {
+            let terms: Vec<(f64, String)> = (0..self.bins.len()).map(|j| (1.0, x(i, j))).collect();
+            write_row(out, &format!(" assign_{}:", i), &terms, "= 1")?;
+        }
+        for j in 0..self.bins.len() {
+            for d in 0..self.problem.schema.len() {
+                let (mut terms, rhs) = self.capacity_row(j, d);
+                if !self.is_existing(j) {
+                    terms.push((-(self.limit(j, d) as f64), y(j)));
+                }
+                write_row(out, &format!(" cap_{}_{}:", j, d), &terms, &format!("<= {}", rhs))?;
+            }
+        }
+        for (j, k) in self.symmetric_pairs() {
+            writeln!(out, " order_{}: {} - {} >= 0", k, y(j), y(k))?;
+        }
+
+        writeln!(out, "Binary")?;
+        for j in 0..self.bins.len() {
+            for i in 0..self.items.len() {
+                writeln!(out, " {}", x(i, j))?;
+            }
+        }
+        for j in self.new_bins() {
+            writeln!(out, " {}", y(j))?;
+        }
+        writeln!(out, "End")
+    }
+
+    /// Writes the model in free MPS format.
+    pub fn write_mps(&self, out: &mut impl Write) -> io::Result<()> {
+        let dimensions = self.problem.schema.len();
+        writeln!(out, "NAME binpacking")?;
+        writeln!(out, "ROWS")?;
+        writeln!(out, " N obj")?;
+        for i in 0..self.items.len() {
+            writeln!(out, " E assign_{}", i)?;
+        }
+        for j in 0..self.bins.len() {
+            for d in 0..dimensions {
+                writeln!(out, " L cap_{}_{}", j, d)?;
+            }
+        }
+        for (_, k) in self.symmetric_pairs() {
+            writeln!(out, " G order_{}", k)?;
+        }
+
+        writeln!(out, "COLUMNS")?;
+        writeln!(out, " MARKER 'MARKER' 'INTORG'")?;
+        for j in 0..self.bins.len() {
+            for (i, &item) in self.items.iter().enumerate() {
+                writeln!(out, " {} assign_{} 1", x(i, j), i)?;
+                for (d, &demand) in self.problem.items[item].demand.iter().enumerate() {
+                    if demand != 0 {
+                        writeln!(out, " {} cap_{}_{} {}", x(i, j), j, d, demand)?;
+                    }
+                }
+            }
+        }
+        let pairs = self.symmetric_pairs();
+        for j in self.new_bins() {
+            if self.costs[j] != 0.0 {
+                writeln!(out, " {} obj {}", y(j), self.costs[j])?;
+            }
+            for d in 0..dimensions {
+                writeln!(out, " {} cap_{}_{} -{}", y(j), j, d, self.limit(j, d))?;
+            }
+            for &(a, b) in &pairs {
+                if a == j {
+                    writeln!(out, " {} order_{} 1", y(j), b)?;
+                } else if b == j {
+                    writeln!(out, " {} order_{} -1", y(j), b)?;
+                }
+            }
+        }
+        writeln!(out, " MARKER 'MARKER' 'INTEND'")?;
+
+        writeln!(out, "RHS")?;
+        for i in 0..self.items.len() {
+            writeln!(out, " RHS assign_{} 1", i)?;
+        }
+        for j in 0..self.bins.len() {
+            for d in 0..dimensions {
+                let (_, rhs) = self.capacity_row(j, d);
+                if rhs != 0 {
+                    writeln!(out, " RHS cap_{}_{} {}", j, d, rhs)?;
+                }
+            }
+        }
+
+        writeln!(out, "BOUNDS")?;
+        for j in 0..self.bins.len() {
+            for i in 0..self.items.len() {
+                writeln!(out, " BV BND {}", x(i, j))?;
+            }
+        }
+        for j in self.new_bins() {
+            writeln!(out, " BV BND {}", y(j))?;
+        }
+        writeln!(out, "ENDATA")
+    }
+
+    /// Reads a solution for this model and turns it into a checked
+    /// [`PackResult`].
+    ///
+    /// Accepts the plain-text solution files of common solvers: any line
+    /// containing a variable name of the model followed by its value, such as
+    /// `x_3_1 1` (Gurobi, HiGHS, SCIP) or `12 x_3_1 1 0` (CBC). Other lines
+    /// and variables at zero may be omitted. Fails with
+    /// [`PackError::InvalidSolution`] if an item is not assigned exactly once
+    /// or a bin's capacity is exceeded.
+    pub fn parse_solution(&self, solution: &str) -> Result<PackResult, PackError> {
+        let mut names: HashMap<String, (usize, usize)> = HashMap::new();
+        for i in 0..self.items.len() {
+            for j in 0..self.bins.len() {
+                names.insert(x(i, j), (i, j));
+            }
+        }
+
+        let mut assigned: Vec<Option<usize>> = vec![None; self.items.len()];
+        for line in solution.lines() {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let Some(position) = tokens.iter().position(|token| names.contains_key(*token)) else { continue };
+            let Some(value) = tokens.get(position + 1).and_then(|v| v.parse::<f64>().ok()) else { continue };
+            if value < 0.5 {
+                continue;
+            }
+            let (i, j) = names[tokens[position]];
+            if assigned[i].is_some_and(|other| other != j) {
+                return Err(invalid(format!("item {} is assigned to more than one bin", self.item(i).id)));
+            }
+            assigned[i] = Some(j);
+        }
+
+        let mut bins = self.bins.clone();
+        for (i, j) in assigned.into_iter().enumerate() {
+            let item = self.item(i);
+            let Some(j) = j else {
+                return Err(invalid(format!("item {} is not assigned to a bin", item.id)));
+            };
+            if !bins[j].add_item(item.clone(), &self.options) {
+                return Err(invalid(format!("item {} exceeds the capacity of bin {}", item.id, j)));
+            }
+        }
+
+        let existing_bins = self.problem.existing_bins.len();
+        ffd::drop_empty_new_bins(&mut bins, existing_bins);
+        ffd::downsize(&mut bins[existing_bins..], self.problem, &self.options);
+        let placed: Vec<&Item> = self.items.iter().map(|&i| &self.problem.items[i]).collect();
+        Ok(PackResult {
+            cost: ffd::total_cost(&bins[existing_bins..], self.problem),
+            lower_bound: crate::bounds::for_items(&placed, self.problem, &self.options).map(|bounds| bounds.best()),
+            bins,
+            existing_bins,
+            unplaced: self.unplaced.clone(),
+        })
+    }
+
+    fn item(&self, i: usize) -> &Item {
+        &self.problem.items[self.items[i]]
+    }
+
+    fn is_existing(&self, j: usize) -> bool {
+        j < self.problem.existing_bins.len()
+    }
+
+    fn new_bins(&self) -> impl Iterator<Item = usize> {
+        self.problem.existing_bins.len()..self.bins.len()
+    }
+
+    fn limit(&self, j: usize, d: usize) -> u64 {
+        self.bins[j].limit(d, &self.options)
+    }
+
+    // Demand terms of the capacity row of bin `j` in dimension `d`, and its
+    // right-hand side: the free space of an existing bin, 0 for a new one.
+    fn capacity_row(&self, j: usize, d: usize) -> (Vec<(f64, String)>, u64) {
+        let terms = (0..self.items.len())
+            .filter(|&i| self.item(i).demand[d] != 0)
+            .map(|i| (self.item(i).demand[d] as f64, x(i, j)))
+            .collect();
+        let rhs = if self.is_existing(j) {
+            self.limit(j, d).saturating_sub(self.bins[j].used(d))
+        } else {
+            0
+        };
+        (terms, rhs)
+    }
+
+    // Consecutive new bins of the same type, which are interchangeable.
+    fn symmetric_pairs(&self) -> Vec<(usize, usize)> {
+        self.new_bins()
+            .zip(self.new_bins().skip(1))
+            .filter(|&(j, k)| self.bins[j].bin_type == self.bins[k].bin_type)
+            .collect()
+    }
+}
+
+fn x(i: usize, j: usize) -> String {
+    format!("x_{}_{}", i, j)
+}
+
+fn y(j: usize) -> String {
+    format!("y_{}", j)
+}
+
+fn invalid(reason: String) -> PackError {
+    PackError::InvalidSolution(reason)
+}
+
+// Writes `label terms... rhs`, breaking long rows over several lines.
+fn write_row(out: &mut impl Write, label: &str, terms: &[(f64, String)], rhs: &str) -> io::Result<()> {
+    write!(out, "{}", label)?;
+    if terms.is_empty() {
+        write!(out, " 0")?;
+    }
+    for (n, (coefficient, name)) in terms.iter().enumerate() {
+        if n > 0 && n % TERMS_PER_LINE == 0 {
+            write!(out, "\n  ")?;
+        }
+        let sign = if *coefficient < 0.0 { '-' } else { '+' };
+        if n == 0 && sign == '+' {
+            write!(out, " {} {}", coefficient, name)?;
+        } else {
+            write!(out, " {} {} {}", sign, coefficient.abs(), name)?;
+        }
+    }
+    if rhs.is_empty() {
+        writeln!(out)
+    } else {
+        writeln!(out, " {}", rhs)
+    }
+}
@@ -0,0 +1,447 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::bin::{self, Bin};
+use crate::item::Item;
+use crate::options::{CapacityMode, PackOptions};
+
+// Allowance for rounding when comparing slack bounds with exact slack, so a
+// bound never prunes the bin it is meant to admit.
+const SLACK_TOLERANCE: f64 = 1e-9;
+
+// Free capacity vectors kept per node to bound what fits below it.
+const ENVELOPES: usize = 4;
+
+// Bins a slack-ordered lookup tries without finding one that takes the item
+// before it falls back to searching the tree.
+const SCAN_LIMIT: usize = 32;
+
+// Whether a bin the item fits in may take it, by index.
+pub(crate) type Allowed<'a> = &'a dyn Fn(usize) -> bool;
+
+/// A segment tree over a list of bins for finding the bin an item goes into
+/// without trying every bin, with the bins also kept in order of slack
+/// (weighted fraction of capacity free).
+///
+/// Each node keeps, over the bins below it, a few free capacity vectors that
+/// together bound every bin's free capacity, the least and most capacity in
+/// each dimension, and the least and most slack. A subtree is skipped when
+/// no vector holds the item, when even its most slack is less than the
+/// item's share of the largest capacities, or when its slack bounds show it
+/// cannot beat the best bin found so far. Bins without room for the least
+/// demand still to come are left out altogether.
+///
+/// First-fit lookups search the tree in index order. Best- and worst-fit
+/// lookups try bins in order of slack from the end that wins, skipping bins
+/// left with the same slack as one already tried, and fall back to the tree
+/// after `SCAN_LIMIT` bins that cannot take the item.
+///
+/// With one dimension all three lookups take logarithmic time. With several,
+/// neither the bounds nor the slack order capture every combination of free
+/// capacities, so a lookup may still descend into subtrees where the item
+/// fits nowhere; it stays far below trying every bin, but the nodes it
+/// visits grow faster than logarithmically with the number of bins open.
+#[derive(Debug, Clone)]
+pub(crate) struct BinIndex {
+    dimensions: usize,
+    // Number of bins indexed, then the number of leaves, a power of two.
+    len: usize,
+    size: usize,
+    // Per node, `dimensions` entries each, with node 1 the root and the
+    // leaves from `size` on.
+    min_capacity: Vec<u32>,
+    max_capacity: Vec<u32>,
+    // Per node, up to ENVELOPES free capacity vectors, each at least the
+    // free capacity of some bins below it, that between them cover all of
+    // those bins. `envelope_counts` says how many are in use.
+    envelopes: Vec<u64>,
+    envelope_counts: Vec<usize>,
+    // Reused buffer for merging envelopes.
+    scratch: Vec<u64>,
+    // Least demand per dimension of any item still to be looked up; bins
+    // without room for it are left out.
+    floor: Vec<u32>,
+    min_slack: Vec<f64>,
+    max_slack: Vec<f64>,
+    // The bins not left out, grouped by slack and then by free capacity and
+    // capacity in each dimension, which settle the slack left once an item
+    // is added. Slack is never negative, so its bits order like its value.
+    by_slack: BTreeMap<(u64, Vec<u64>), BTreeSet<usize>>,
+    // The group of each bin in `by_slack`, if it is there.
+    slack_keys: Vec<Option<(u64, Vec<u64>)>>,
+}
+
+impl BinIndex {
+    pub(crate) fn new(bins: &[Bin], dimensions: usize, options: &PackOptions) -> Self {
+        let size = bins.len().next_power_of_two().max(1);
+        let mut index = BinIndex {
+            dimensions,
+            len: bins.len(),
+            size,
+            min_capacity: vec![u32::MAX; 2 * size * dimensions],
+            max_capacity: vec![0; 2 * size * dimensions],
+            envelopes: vec![0; 2 * size * ENVELOPES * dimensions],
+            envelope_counts: vec![0; 2 * size],
+            scratch: Vec::with_capacity(2 * ENVELOPES * dimensions),
+            floor: vec![0; dimensions],
+            min_slack: vec![f64::INFINITY; 2 * size],
+            max_slack: vec![f64::NEG_INFINITY; 2 * size],
+            by_slack: BTreeMap::new(),
+            slack_keys: vec![None; size],
+        };
+        for (b, bin) in bins.iter().enumerate() {
+            index.set_leaf(b, bin, options);
+        }
+        for node in (1..size).rev() {
+            index.pull(node);
+        }
+        index
+    }
+
+    /// Refreshes bin `b` after its items changed, or indexes it if it is the
+    /// bin just pushed onto `bins`.
+    pub(crate) fn update(&mut self, bins: &[Bin], b: usize, options: &PackOptions) {
+        if b >= self.size {
+            let floor = std::mem::take(&mut self.floor);
+            *self = BinIndex::new(bins, self.dimensions, options);
+            self.floor = floor;
+            return;
+        }
+        self.len = self.len.max(b + 1);
+        self.set_leaf(b, &bins[b], options);
+        let mut node = (self.size + b) / 2;
+        while node >= 1 {
+            self.pull(node);
+            node /= 2;
+        }
+    }
+
+    /// Declares that every item looked up from now on demands at least
+    /// `floor` in each dimension, so bins without that much room can be
+    /// dropped as they are updated.
+    pub(crate) fn raise_floor(&mut self, floor: &[u32]) {
+        self.floor.copy_from_slice(floor);
+    }
+
+    // Each lookup only returns bins that `item` fits in and that `allowed`
+    // accepts; `allowed` is only asked about bins the item fits in.
+
+    /// The first bin from `from` on that takes `item`.
+    pub(crate) fn first_fit(&self, bins: &[Bin], item: &Item, options: &PackOptions, allowed: Allowed<'_>, from: usize) -> Option<usize> {
+        self.first_fit_below(1, 0, self.size, bins, item, options, allowed, from)
+    }
+
+    /// The bin that takes `item` with the least slack left afterwards; ties
+    /// go to the lowest index.
+    pub(crate) fn best_fit(&self, bins: &[Bin], item: &Item, options: &PackOptions, allowed: Allowed<'_>) -> Option<usize> {
+        // Try bins from the least slack that can hold the item up, until the
+        // slack left in the rest cannot beat the best so far.
+        let (least, most) = self.shares(item, options);
+        let mut best: Option<(f64, usize)> = None;
+        let mut misses = 0;
+        'groups: for ((key, _), group) in self.by_slack.range((least.to_bits(), Vec::new())..) {
+            if best.is_some_and(|(slack, _)| f64::from_bits(*key) - most > slack + SLACK_TOLERANCE) {
+                break;
+            }
+            // The rest of a group would be left with the same slack as its
+            // first bin that takes the item, at a higher index.
+            for &b in group {
+                if bins[b].fits(item, options) && allowed(b) {
+                    let slack = bins[b].slack_after(item, options);
+                    if best.is_none_or(|best| (slack, b) < best) {
+                        best = Some((slack, b));
+                    }
+                    continue 'groups;
+                }
+                misses += 1;
+                if misses == SCAN_LIMIT {
+                    self.best_fit_below(1, 0, self.size, bins, item, options, allowed, &mut best);
+                    break 'groups;
+                }
+            }
+        }
+        best.map(|(_, b)| b)
+    }
+
+    /// The bin other than `exclude` that takes `item` with the most slack
+    /// left afterwards; ties go to the lowest index.
+    pub(crate) fn worst_fit(
+        &self,
+        bins: &[Bin],
+        item: &Item,
+        options: &PackOptions,
+        allowed: Allowed<'_>,
+        exclude: Option<usize>,
+    ) -> Option<usize> {
+        // Try bins from the most slack down, until the rest cannot hold the
+        // item or beat the best so far. Under the strict capacity mode the
+        // item takes at least its share of the largest capacities.
+        let (least, _) = self.shares(item, options);
+        let taken = if matches!(options.capacity_mode, CapacityMode::Strict(_)) { least } else { 0.0 };
+        let mut best: Option<(f64, usize)> = None;
+        let mut misses = 0;
+        'groups: for ((key, _), group) in self.by_slack.range((least.to_bits(), Vec::new())..).rev() {
+            if best.is_some_and(|(slack, _)| f64::from_bits(*key) - taken < slack - SLACK_TOLERANCE) {
+                break;
+            }
+            for &b in group {
+                if exclude != Some(b) && bins[b].fits(item, options) && allowed(b) {
+                    let slack = bins[b].slack_after(item, options);
+                    if best.is_none_or(|(best_slack, best_b)| slack > best_slack || (slack == best_slack && b < best_b)) {
+                        best = Some((slack, b));
+                    }
+                    continue 'groups;
+                }
+                misses += 1;
+                if misses == SCAN_LIMIT {
+                    self.worst_fit_below(1, 0, self.size, bins, item, options, allowed, exclude, &mut best);
+                    break 'groups;
+                }
+            }
+        }
+        best.map(|(_, b)| b)
+    }
+
+    // Bounds on the share of a bin's capacity `item` takes, as slack: the
+    // least any bin that takes it must have free, allowing for rounding, and
+    // the most it can take from any bin.
+    fn shares(&self, item: &Item, options: &PackOptions) -> (f64, f64) {
+        let (max_capacity, min_capacity) = (&self.max_capacity[self.dimensions..2 * self.dimensions], &self.min_capacity[self.dimensions..2 * self.dimensions]);
+        let share = |capacity: &[u32], demand: &dyn Fn(usize) -> f64| -> f64 {
+            (0..self.dimensions).map(|d| options.weights[d] as f64 * demand(d) / capacity[d].max(1) as f64).sum()
+        };
+        let least = share(max_capacity, &|d| bin::needed(item.demand[d], d, options)) * (1.0 - 1e-6) - SLACK_TOLERANCE;
+        let most = share(min_capacity, &|d| item.demand[d] as f64);
+        (least.max(0.0), most)
+    }
+
+    // Nodes cover the leaves `start..end`.
+    #[allow(clippy::too_many_arguments)]
+    fn first_fit_below(
+        &self,
+        node: usize,
+        start: usize,
+        end: usize,
+        bins: &[Bin],
+        item: &Item,
+        options: &PackOptions,
+        allowed: Allowed<'_>,
+        from: usize,
+    ) -> Option<usize> {
+        if end <= from || start >= self.len || !self.may_fit(node, item, options) {
+            return None;
+        }
+        if end - start == 1 {
+            return (bins[start].fits(item, options) && allowed(start)).then_some(start);
+        }
+        let middle = (start + end) / 2;
+        self.first_fit_below(2 * node, start, middle, bins, item, options, allowed, from)
+            .or_else(|| self.first_fit_below(2 * node + 1, middle, end, bins, item, options, allowed, from))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn best_fit_below(
+        &self,
+        node: usize,
+        start: usize,
+        end: usize,
+        bins: &[Bin],
+        item: &Item,
+        options: &PackOptions,
+        allowed: Allowed<'_>,
+        best: &mut Option<(f64, usize)>,
+    ) {
+        if start >= self.len || !self.may_fit(node, item, options) {
+            return;
+        }
+        if best.is_some_and(|(slack, _)| self.least_slack_after(node, item, options) > slack + SLACK_TOLERANCE) {
+            return;
+        }
+        if end - start == 1 {
+            if bins[start].fits(item, options) && allowed(start) {
+                let slack = bins[start].slack_after(item, options);
+                if best.is_none_or(|best| (slack, start) < best) {
+                    *best = Some((slack, start));
+                }
+            }
+            return;
+        }
+        // The child with the least slack first, so the other is more likely
+        // to be pruned.
+        let middle = (start + end) / 2;
+        let (left, right) = ((2 * node, start, middle), (2 * node + 1, middle, end));
+        let (first, second) = if self.min_slack[right.0] < self.min_slack[left.0] { (right, left) } else { (left, right) };
+        self.best_fit_below(first.0, first.1, first.2, bins, item, options, allowed, best);
+        self.best_fit_below(second.0, second.1, second.2, bins, item, options, allowed, best);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn worst_fit_below(
+        &self,
+        node: usize,
+        start: usize,
+        end: usize,
+        bins: &[Bin],
+        item: &Item,
+        options: &PackOptions,
+        allowed: Allowed<'_>,
+        exclude: Option<usize>,
+        best: &mut Option<(f64, usize)>,
+    ) {
+        if start >= self.len || !self.may_fit(node, item, options) {
+            return;
+        }
+        // Slack only falls when the item is added.
+        if best.is_some_and(|(slack, _)| self.max_slack[node] < slack - SLACK_TOLERANCE) {
+            return;
+        }
+        if end - start == 1 {
+            if exclude != Some(start) && bins[start].fits(item, options) && allowed(start) {
+                let slack = bins[start].slack_after(item, options);
+                if best.is_none_or(|(best_slack, b)| slack > best_slack || (slack == best_slack && start < b)) {
+                    *best = Some((slack, start));
+                }
+            }
+            return;
+        }
+        let middle = (start + end) / 2;
+        let (left, right) = ((2 * node, start, middle), (2 * node + 1, middle, end));
+        let (first, second) = if self.max_slack[right.0] > self.max_slack[left.0] { (right, left) } else { (left, right) };
+        self.worst_fit_below(first.0, first.1, first.2, bins, item, options, allowed, exclude, best);
+        self.worst_fit_below(second.0, second.1, second.2, bins, item, options, allowed, exclude, best);
+    }
+
+    // Whether some bin below `node` may have room for `item`: the most slack
+    // must cover the item's weighted share of the largest capacities, and
+    // some envelope must hold it.
+    fn may_fit(&self, node: usize, item: &Item, options: &PackOptions) -> bool {
+        let capacity = &self.max_capacity[node * self.dimensions..(node + 1) * self.dimensions];
+        let share: f64 = (0..self.dimensions)
+            .map(|d| options.weights[d] as f64 * bin::needed(item.demand[d], d, options) / capacity[d].max(1) as f64)
+            .sum();
+        // Weighted fits compare in f32, so allow for its rounding too.
+        if self.max_slack[node] < share * (1.0 - 1e-6) - SLACK_TOLERANCE {
+            return false;
+        }
+        (0..self.envelope_counts[node]).any(|e| {
+            let free = self.envelope(node, e);
+            free.iter().zip(&item.demand).enumerate().all(|(d, (&free, &demand))| bin::admits(free, demand, d, options))
+        })
+    }
+
+    fn envelope(&self, node: usize, e: usize) -> &[u64] {
+        let start = (node * ENVELOPES + e) * self.dimensions;
+        &self.envelopes[start..start + self.dimensions]
+    }
+
+    // A lower bound on the slack any bin below `node` has once `item` is
+    // added: its slack less the item's weighted share of the smallest
+    // capacities.
+    fn least_slack_after(&self, node: usize, item: &Item, options: &PackOptions) -> f64 {
+        let capacity = &self.min_capacity[node * self.dimensions..(node + 1) * self.dimensions];
+        let share: f64 = (0..self.dimensions)
+            .map(|d| options.weights[d] as f64 * item.demand[d] as f64 / capacity[d].max(1) as f64)
+            .sum();
+        self.min_slack[node] - share
+    }
+
+    fn set_leaf(&mut self, b: usize, bin: &Bin, options: &PackOptions) {
+        let node = self.size + b;
+        if let Some(key) = self.slack_keys[b].take() {
+            let group = self.by_slack.get_mut(&key).expect("indexed bin");
+            group.remove(&b);
+            if group.is_empty() {
+                self.by_slack.remove(&key);
+            }
+        }
+        if (0..self.dimensions).any(|d| !bin::admits(bin.free(d, options), self.floor[d], d, options)) {
+            self.envelope_counts[node] = 0;
+            self.min_slack[node] = f64::INFINITY;
+            self.max_slack[node] = f64::NEG_INFINITY;
+            return;
+        }
+        let mut slack = 0.0;
+        let mut state = Vec::with_capacity(2 * self.dimensions);
+        for d in 0..self.dimensions {
+            let free = bin.free(d, options);
+            self.envelopes[node * ENVELOPES * self.dimensions + d] = free;
+            self.min_capacity[node * self.dimensions + d] = bin.capacity[d];
+            self.max_capacity[node * self.dimensions + d] = bin.capacity[d];
+            slack += options.weights[d] as f64 * free as f64 / bin.capacity[d] as f64;
+            state.push(free);
+        }
+        state.extend(bin.capacity.iter().map(|&c| c as u64));
+        self.envelope_counts[node] = 1;
+        self.by_slack.entry((slack.to_bits(), state.clone())).or_default().insert(b);
+        self.slack_keys[b] = Some((slack.to_bits(), state));
+        self.min_slack[node] = slack;
+        self.max_slack[node] = slack;
+    }
+
+    fn pull(&mut self, node: usize) {
+        let (left, right) = (2 * node, 2 * node + 1);
+        for d in 0..self.dimensions {
+            let (l, r) = (left * self.dimensions + d, right * self.dimensions + d);
+            self.min_capacity[node * self.dimensions + d] = self.min_capacity[l].min(self.min_capacity[r]);
+            self.max_capacity[node * self.dimensions + d] = self.max_capacity[l].max(self.max_capacity[r]);
+        }
+        self.merge_envelopes(node);
+        self.min_slack[node] = self.min_slack[left].min(self.min_slack[right]);
+        self.max_slack[node] = self.max_slack[left].max(self.max_slack[right]);
+    }
+
+    // Envelopes of `node` from those of its children: the ones not covered
+    // by another, with the closest pairs combined into their componentwise
+    // maximum until at most ENVELOPES remain.
+    fn merge_envelopes(&mut self, node: usize) {
+        let dimensions = self.dimensions;
+        let mut candidates = std::mem::take(&mut self.scratch);
+        candidates.clear();
+        for child in [2 * node, 2 * node + 1] {
+            for e in 0..self.envelope_counts[child] {
+                candidates.extend_from_slice(self.envelope(child, e));
+            }
+        }
+        let at = |e: usize| e * dimensions..(e + 1) * dimensions;
+        let mut count = candidates.len() / dimensions;
+
+        let mut e = 0;
+        while e < count {
+            let covered = (0..count).any(|f| {
+                let (a, b) = (&candidates[at(f)], &candidates[at(e)]);
+                f != e && a.iter().zip(b).all(|(x, y)| x >= y) && (f < e || a != b)
+            });
+            if covered {
+                count -= 1;
+                candidates.copy_within(at(count), e * dimensions);
+            } else {
+                e += 1;
+            }
+        }
+
+        let capacity = &self.max_capacity[node * dimensions..(node + 1) * dimensions];
+        while count > ENVELOPES {
+            let mut closest = (f64::INFINITY, 0, 1);
+            for a in 0..count {
+                for b in a + 1..count {
+                    let gap: f64 = (0..dimensions)
+                        .map(|d| candidates[a * dimensions + d].abs_diff(candidates[b * dimensions + d]) as f64 / capacity[d].max(1) as f64)
+                        .sum();
+                    if gap < closest.0 {
+                        closest = (gap, a, b);
+                    }
+                }
+            }
+            let (_, a, b) = closest;
+            for d in 0..dimensions {
+                candidates[a * dimensions + d] = candidates[a * dimensions + d].max(candidates[b * dimensions + d]);
+            }
+            count -= 1;
+            candidates.copy_within(at(count), b * dimensions);
+        }
+
+        self.envelope_counts[node] = count;
+        let start = node * ENVELOPES * dimensions;
+        self.envelopes[start..start + count * dimensions].copy_from_slice(&candidates[..count * dimensions]);
+        self.scratch = candidates;
+    }
+}
@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+
+/// A workload to be packed, described by its demand in each dimension of the
+/// [`ResourceSchema`](crate::ResourceSchema).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Item {
+    /// Caller-chosen identifier, unique within a [`Problem`](crate::Problem).
+    pub id: String,
+    /// Amount required per dimension, in schema order.
+    pub demand: Vec<u32>,
+    /// Arbitrary caller data carried through to the result untouched.
+    pub metadata: BTreeMap<String, String>,
+    /// Labels a bin must carry, with these values, to take the item.
+    pub required_labels: BTreeMap<String, String>,
+    /// Labels the item would rather have on its bin. Best-fit style
+    /// strategies favour bins carrying more of them; other strategies ignore
+    /// them.
+    pub preferred_labels: BTreeMap<String, String>,
+    /// Importance relative to other items, higher first; 0 by default.
+    /// [`preempt`](crate::preempt) evicts lower-priority items to make room
+    /// for higher ones.
+    pub priority: i32,
+    /// What placing the item is worth to [`pack_fixed`](crate::pack_fixed);
+    /// 1 by default.
+    pub value: u64,
+}
+
+impl Item {
+    pub fn new(id: impl Into<String>, demand: impl Into<Vec<u32>>) -> Self {
+        Item {
+            id: id.into(),
+            demand: demand.into(),
+            metadata: BTreeMap::new(),
+            required_labels: BTreeMap::new(),
+            preferred_labels: BTreeMap::new(),
+            priority: 0,
+            value: 1,
+        }
+    }
+
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Only places the item in bins labelled `key=value`, e.g.
+    /// `disk_type=ssd`.
+    pub fn with_required_label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.required_labels.insert(key.into(), value.into());
+        self
+    }
+
+    /// Favours bins labelled `key=value`, e.g. `zone=a`, where the strategy
+    /// allows.
+    pub fn with_preferred_label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.preferred_labels.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_value(mut self, value: u64) -> Self {
+        self.value = value;
+        self
+    }
+}
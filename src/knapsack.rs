@@ -0,0 +1,229 @@
+use std::collections::HashSet;
+
+use crate::bin::Bin;
+use crate::bounds;
+use crate::constraint::ConstraintSet;
+use crate::error::PackError;
+use crate::ffd::{self, weighted_size};
+use crate::index::BinIndex;
+use crate::item::Item;
+use crate::options::{CapacityMode, PackOptions};
+use crate::problem::Problem;
+use crate::result::{PackResult, Unplaced, UnplacedReason};
+use crate::strategy::PlacementContext;
+
+/// What [`pack_fixed`] maximises.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Maximise {
+    /// Total [`value`](Item::value) of the placed items.
+    #[default]
+    Value,
+    /// Number of placed items.
+    Count,
+}
+
+impl Maximise {
+    fn of(self, item: &Item) -> u64 {
+        match self {
+            Maximise::Value => item.value,
+            Maximise::Count => 1,
+        }
+    }
+}
+
+/// Outcome of [`pack_fixed`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixedPacking {
+    /// The packing, whose `unplaced` items are the ones rejected. Bins of
+    /// the fleet left empty are not listed.
+    pub result: PackResult,
+    /// Total value, or number, of the items placed.
+    pub objective: u64,
+    /// Upper bound on the objective of any packing into the fleet.
+    pub upper_bound: u64,
+}
+
+impl FixedPacking {
+    /// How much the objective might still be improved on.
+    pub fn gap(&self) -> u64 {
+        self.upper_bound.saturating_sub(self.objective)
+    }
+}
+
+/// Places as much of `problem`'s items as fit into a fleet of fixed size,
+/// maximising their total value or their number: a multiple knapsack
+/// problem. The fleet is the existing bins, with whatever they already hold,
+/// and `max_count` bins of every bin type; bin costs are not considered.
+///
+/// Items are placed greedily with `options.strategy`, once in order of value
+/// per weighted size and once in order of value, largest first. Each
+/// rejected item, most valuable first, then takes the place of the least
+/// valuable placed item it fits instead of, which moves to any bin with room
+/// or is rejected in turn. The better of the two packings is returned, with
+/// an upper bound on the best objective possible: the least, over
+/// dimensions, of the value that fits when items may be split and the
+/// fleet's free capacity pooled.
+///
+/// Fails if the problem is empty or inconsistent with its schema, the
+/// weights or overcommit ratios in `options` are invalid, a bin type has no
+/// `max_count`, `options` uses [`CapacityMode::Weighted`], or the problem
+/// has constraints.
+///
+/// ```
+/// use bin_packer::{pack_fixed, BinType, Item, Maximise, PackOptions, Problem, ResourceSchema};
+///
+/// let fleet = vec![BinType::new("server", [16, 64]).with_max_count(2)];
+/// let items = (0..6).map(|i| Item::new(format!("job-{}", i), [8, 16]).with_value(10 + i));
+/// let problem = Problem::from_bin_types(ResourceSchema::new(["cores", "memory_gb"]), fleet).with_items(items);
+/// let packing = pack_fixed(&problem, &PackOptions::new([0.5, 0.5]), Maximise::Value)?;
+/// assert_eq!(packing.result.unplaced.len(), 2);
+/// assert_eq!(packing.objective, 12 + 13 + 14 + 15);
+/// assert_eq!(packing.gap(), 0);
+/// # Ok::<(), bin_packer::PackError>(())
+/// ```
+pub fn pack_fixed(problem: &Problem, options: &PackOptions, maximise: Maximise) -> Result<FixedPacking, PackError> {
+    problem.validate()?;
+    let options = &options.normalized(&problem.schema)?;
+    if matches!(options.capacity_mode, CapacityMode::Weighted) {
+        return Err(PackError::Unsupported("the fixed fleet mode needs the strict capacity mode".to_string()));
+    }
+    if !problem.constraints.is_empty() {
+        return Err(PackError::Unsupported("the fixed fleet mode does not support item constraints".to_string()));
+    }
+    if let Some(bin_type) = problem.bin_types.iter().find(|bin_type| bin_type.max_count.is_none()) {
+        return Err(PackError::Unsupported(format!("the fixed fleet mode needs a max_count for bin type {:?}", bin_type.name)));
+    }
+
+    let mut fleet = problem.existing_bins.clone();
+    for (t, bin_type) in problem.bin_types.iter().enumerate() {
+        fleet.extend(std::iter::repeat_n(Bin::of_type(t, bin_type), bin_type.max_count.unwrap_or(0)));
+    }
+    let reference = problem.reference_capacity();
+    let size = |item: &Item| weighted_size(item, &reference, options);
+    let value = |item: &Item| maximise.of(item) as f64;
+
+    let mut by_density: Vec<&Item> = problem.items.iter().collect();
+    by_density.sort_by(|a, b| (value(b) * size(a)).total_cmp(&(value(a) * size(b))).then(value(b).total_cmp(&value(a))));
+    let mut by_value: Vec<&Item> = problem.items.iter().collect();
+    by_value.sort_by(|a, b| value(b).total_cmp(&value(a)).then(size(a).total_cmp(&size(b))));
+
+    let movable: HashSet<&str> = problem.items.iter().map(|item| item.id.as_str()).collect();
+    let mut best: Option<(u64, Vec<Bin>, Vec<Item>)> = None;
+    for order in [by_density, by_value] {
+        let mut bins = fleet.clone();
+        let rejected = place(&mut bins, &order, problem.schema.len(), options)?;
+        let rejected = trade(&mut bins, rejected, &movable, maximise, options);
+        let objective = bins.iter().flat_map(|bin| bin.items()).filter(|item| movable.contains(item.id.as_str())).map(|item| maximise.of(item)).sum();
+        if best.as_ref().is_none_or(|(best_objective, _, _)| objective > *best_objective) {
+            best = Some((objective, bins, rejected));
+        }
+    }
+    let (objective, mut bins, rejected) = best.expect("two orders were tried");
+
+    let existing_bins = problem.existing_bins.len();
+    ffd::drop_empty_new_bins(&mut bins, existing_bins);
+    let empty_bins: Vec<Bin> = problem.bin_types.iter().enumerate().map(|(t, bt)| Bin::of_type(t, bt)).collect();
+    let unplaced: Vec<Unplaced> = rejected
+        .into_iter()
+        .map(|item| {
+            let reason = if empty_bins.iter().chain(&fleet).any(|bin| bin.fits(&item, options)) {
+                UnplacedReason::NoBinAvailable
+            } else {
+                ffd::oversized(&empty_bins, &item, problem, options)
+            };
+            Unplaced { item, reason }
+        })
+        .collect();
+    let rejected_ids: HashSet<&str> = unplaced.iter().map(|u| u.item.id.as_str()).collect();
+    let placed: Vec<&Item> = problem.items.iter().filter(|item| !rejected_ids.contains(item.id.as_str())).collect();
+    let result = PackResult {
+        cost: ffd::total_cost(&bins[existing_bins..], problem),
+        lower_bound: bounds::for_items(&placed, problem, options).map(|bounds| bounds.best()),
+        bins,
+        existing_bins,
+        unplaced,
+    };
+    let upper_bound = upper_bound(&fleet, &problem.items, problem.schema.len(), maximise, options).max(objective);
+    Ok(FixedPacking { result, objective, upper_bound })
+}
+
+// Places `order` into `bins` with the strategy of `options` and returns the
+// items that fit in none.
+fn place<'a>(bins: &mut [Bin], order: &[&'a Item], dimensions: usize, options: &PackOptions) -> Result<Vec<&'a Item>, PackError> {
+    let constraints = ConstraintSet::default();
+    let mut index = BinIndex::new(bins, dimensions, options);
+    let mut rejected = Vec::new();
+    let mut current = None;
+    for &item in order {
+        let members = [item];
+        let context = PlacementContext::new(bins, &index, item, &members, &constraints, options, current);
+        match context.select(options.strategy.as_ref())? {
+            Some(b) => {
+                bins[b].push_item(item.clone());
+                index.update(bins, b, options);
+                current = Some(b);
+            }
+            None => rejected.push(item),
+        }
+    }
+    Ok(rejected)
+}
+
+// Lets each of `rejected`, most valuable first, take the place of the least
+// valuable `movable` item it fits instead of, which then goes into the
+// first bin with room or is rejected. Returns the items left rejected.
+fn trade(bins: &mut [Bin], mut rejected: Vec<&Item>, movable: &HashSet<&str>, maximise: Maximise, options: &PackOptions) -> Vec<Item> {
+    rejected.sort_by_key(|item| std::cmp::Reverse(maximise.of(item)));
+    let mut left = Vec::new();
+    for item in rejected {
+        let mut swap: Option<(u64, usize, usize)> = None;
+        for (b, bin) in bins.iter().enumerate() {
+            for (k, placed) in bin.items().iter().enumerate() {
+                let worth = maximise.of(placed);
+                if worth >= maximise.of(item) || !movable.contains(placed.id.as_str()) || swap.is_some_and(|(least, _, _)| worth >= least) {
+                    continue;
+                }
+                if bin.without(&[k]).fits(item, options) {
+                    swap = Some((worth, b, k));
+                }
+            }
+        }
+        let Some((_, b, k)) = swap else {
+            left.push(item.clone());
+            continue;
+        };
+        let displaced = bins[b].remove_item(k);
+        bins[b].push_item(item.clone());
+        match bins.iter().position(|bin| bin.fits(&displaced, options)) {
+            Some(c) => bins[c].push_item(displaced),
+            None => left.push(displaced),
+        }
+    }
+    left
+}
+
+// The least, over dimensions, of the most value of `items` that fits into
+// the free capacity of `fleet` pooled together, when items may be split.
+// Items that fit in no bin of the fleet count for nothing.
+fn upper_bound(fleet: &[Bin], items: &[Item], dimensions: usize, maximise: Maximise, options: &PackOptions) -> u64 {
+    let fitting: Vec<&Item> = items.iter().filter(|item| fleet.iter().any(|bin| bin.fits(item, options))).collect();
+    let mut bound: f64 = fitting.iter().map(|item| maximise.of(item) as f64).sum();
+    for d in 0..dimensions {
+        let mut free: f64 = fleet.iter().map(|bin| bin.free(d, options) as f64).sum();
+        let mut by_density = fitting.clone();
+        by_density.sort_by(|a, b| (maximise.of(b) as f64 * a.demand[d] as f64).total_cmp(&(maximise.of(a) as f64 * b.demand[d] as f64)));
+        let mut value = 0.0;
+        for item in by_density {
+            let (worth, demand) = (maximise.of(item) as f64, item.demand[d] as f64);
+            if demand <= free {
+                value += worth;
+                free -= demand;
+            } else {
+                value += worth * free / demand;
+                break;
+            }
+        }
+        bound = bound.min(value);
+    }
+    (bound + 1e-9).floor() as u64
+}
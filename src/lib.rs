@@ -0,0 +1,206 @@
+//! Weighted First-Fit Decreasing (FFD) vector bin packing: items with a
+//! demand in each dimension of a [`ResourceSchema`] are packed into bins with
+//! a capacity in each of those dimensions.
+//!
+//! See <https://en.wikipedia.org/wiki/First-fit_bin_packing>.
+//!
+//! ```
+//! use bin_packer::{pack, Item, PackOptions, Problem, ResourceSchema};
+//!
+//! let schema = ResourceSchema::new(["cores", "memory_gb", "gpus"]);
+//! let problem = Problem::new(schema, [32, 256, 4]).with_items([
+//!     Item::new("trainer", [16, 64, 2]),
+//!     Item::new("cache", [8, 200, 0]),
+//!     Item::new("inference", [4, 32, 2]),
+//! ]);
+//! let result = pack(&problem, &PackOptions::new([0.5, 0.3, 0.2]))?;
+//! assert_eq!(result.bins.len(), 2);
+//! assert_eq!(result.bin_of("inference"), Some(0));
+//! # Ok::<(), bin_packer::PackError>(())
+//! ```
+//!
+//! With several bin types, each with a cost and an optional limit on how many
+//! may be opened, the solver picks which type to open for each new bin so as
+//! to minimise total cost:
+//!
+//! ```
+//! use bin_packer::{pack, BinType, Item, PackOptions, Problem, ResourceSchema};
+//!
+//! let schema = ResourceSchema::new(["cores", "memory_gb"]);
+//! let catalogue = vec![
+//!     BinType::new("small", [8, 32]).with_cost(1.0),
+//!     BinType::new("large", [32, 128]).with_cost(3.0).with_max_count(2),
+//! ];
+//! let items = (0..10).map(|i| Item::new(format!("job-{}", i), [4, 16]));
+//! let problem = Problem::from_bin_types(schema, catalogue).with_items(items);
+//! let result = pack(&problem, &PackOptions::new([0.5, 0.5]))?;
+//! assert_eq!(result.cost, 4.0);
+//! # Ok::<(), bin_packer::PackError>(())
+//! ```
+//!
+//! Bins that are already running can be passed in with their items, or just
+//! their reserved usage, and are filled before any new bin is opened:
+//!
+//! ```
+//! use bin_packer::{pack, Bin, Item, PackOptions, Problem, ResourceSchema};
+//!
+//! let schema = ResourceSchema::new(["cores", "memory_gb"]);
+//! let running = Bin::with_reserved([16, 64], [12, 16]);
+//! let problem = Problem::new(schema, [16, 64])
+//!     .with_existing_bins([running])
+//!     .with_items([Item::new("small", [4, 8]), Item::new("big", [8, 8])]);
+//! let result = pack(&problem, &PackOptions::new([0.5, 0.5]))?;
+//! assert_eq!(result.bin_of("small"), Some(0));
+//! assert_eq!(result.new_bins().len(), 1);
+//! # Ok::<(), bin_packer::PackError>(())
+//! ```
+//!
+//! [`Constraint`]s keep items apart, such as the replicas of a service, or
+//! together, such as a service and its sidecar, whatever the strategy:
+//!
+//! ```
+//! use bin_packer::{pack, Constraint, Item, PackOptions, Problem, ResourceSchema};
+//!
+//! let problem = Problem::new(ResourceSchema::new(["cores", "memory_gb"]), [16, 64])
+//!     .with_items([Item::new("api-1", [4, 8]), Item::new("api-2", [4, 8]), Item::new("proxy", [1, 1])])
+//!     .with_constraints([Constraint::anti_affinity(["api-1", "api-2"]), Constraint::affinity(["api-1", "proxy"])]);
+//! let result = pack(&problem, &PackOptions::new([0.5, 0.5]))?;
+//! assert_ne!(result.bin_of("api-1"), result.bin_of("api-2"));
+//! assert_eq!(result.bin_of("api-1"), result.bin_of("proxy"));
+//! # Ok::<(), bin_packer::PackError>(())
+//! ```
+//!
+//! Bins and bin types carry labels, such as a zone or disk type. An item
+//! only goes into bins with the labels it requires, and best-fit style
+//! strategies favour bins with the labels it prefers:
+//!
+//! ```
+//! use bin_packer::{pack, BestFit, Bin, BinType, Item, PackOptions, Problem, ResourceSchema};
+//!
+//! let catalogue = vec![
+//!     BinType::new("hdd", [16, 64]),
+//!     BinType::new("ssd", [16, 64]).with_cost(2.0).with_label("disk_type", "ssd"),
+//! ];
+//! let problem = Problem::from_bin_types(ResourceSchema::new(["cores", "memory_gb"]), catalogue)
+//!     .with_existing_bins([Bin::new([16, 64]).with_label("zone", "a"), Bin::new([16, 64]).with_label("zone", "b")])
+//!     .with_items([
+//!         Item::new("db", [4, 8]).with_required_label("disk_type", "ssd"),
+//!         Item::new("web", [4, 8]).with_preferred_label("zone", "b"),
+//!     ]);
+//! let result = pack(&problem, &PackOptions::new([0.5, 0.5]).with_strategy(BestFit))?;
+//! assert_eq!(result.bins[result.bin_of("db").unwrap()].labels["disk_type"], "ssd");
+//! assert_eq!(result.bin_of("web"), Some(1));
+//! # Ok::<(), bin_packer::PackError>(())
+//! ```
+//!
+//! A [`Topology`] of labels arranges bins into failure domains, such as
+//! racks within zones within regions, over which spread constraints balance
+//! items:
+//!
+//! ```
+//! use bin_packer::{pack, BinType, Constraint, Item, PackOptions, Problem, ResourceSchema, Topology};
+//!
+//! let catalogue = vec![BinType::new("a", [16, 64]).with_label("zone", "a"), BinType::new("b", [16, 64]).with_label("zone", "b")];
+//! let replicas: Vec<String> = (0..4).map(|i| format!("api-{}", i)).collect();
+//! let problem = Problem::from_bin_types(ResourceSchema::new(["cores", "memory_gb"]), catalogue)
+//!     .with_topology(Topology::new(["zone"]))
+//!     .with_items(replicas.iter().map(|id| Item::new(id.as_str(), [2, 4])))
+//!     .with_constraints([Constraint::spread(replicas.clone(), "zone", 1)]);
+//! let result = pack(&problem, &PackOptions::new([0.5, 0.5]))?;
+//! let spread = &result.spread(&problem)[0];
+//! assert_eq!(spread.counts.values().copied().collect::<Vec<_>>(), [2, 2]);
+//! assert!(spread.satisfied());
+//! # Ok::<(), bin_packer::PackError>(())
+//! ```
+//!
+//! Weights only influence the order in which items are placed (see
+//! [`ItemOrdering`]) and how a [`PlacementStrategy`] scores bins. A bin never
+//! holds more than its capacity in any dimension, unless an explicit
+//! overcommit ratio is set with [`PackOptions::with_overcommit`].
+//!
+//! [`pack`] is a fast heuristic. Every [`PackResult`] carries a lower bound on
+//! the bins needed and the resulting [`gap`](PackResult::gap), so it is
+//! clear how far a packing may be from optimal. For small and medium instances,
+//! [`solve_exact`] searches for a packing with the fewest bins by
+//! branch-and-bound and reports whether it proved optimality within its
+//! [`Budget`]. For large instances, [`anneal`] improves on [`pack`] by
+//! simulated annealing. An [`IlpModel`] writes the problem out in LP or MPS
+//! format for an external MIP solver and reads its solution back. When it is
+//! unclear which weights, ordering or strategy suit an instance,
+//! [`multi_start`] tries many combinations in parallel and keeps the best,
+//! while [`sweep`] reports utilisation and stranded capacity for each and
+//! picks out the Pareto-optimal trade-offs.
+//!
+//! A [`Packer`] places items online, one at a time as they arrive, and
+//! releases them again when they leave. [`repack`] plans the moves that
+//! consolidate a running placement into fewer bins. In a fleet of fixed
+//! size, [`preempt`] places items by priority and lists the lower-priority
+//! items to evict where there is no room, while [`pack_fixed`] places as
+//! much value as fits and bounds how much more could.
+//!
+//! [`bin_packing_weighted_ffd`] keeps the original two-dimensional
+//! (cores, disk) interface and fit check, which scales demands by the weights
+//! and can overcommit a bin:
+//!
+//! ```
+//! use bin_packer::{bin_packing_weighted_ffd, Item};
+//!
+//! let items = vec![Item::new("a", [4, 100]), Item::new("b", [6, 150]), Item::new("c", [2, 50])];
+//! let result = bin_packing_weighted_ffd(&items, 10, 200, 0.6, 0.4)?;
+//! assert_eq!(result.bins.len(), 2);
+//! # Ok::<(), bin_packer::PackError>(())
+//! ```
+
+mod anneal;
+mod bin;
+mod bin_type;
+mod bounds;
+mod budget;
+mod constraint;
+mod error;
+mod exact;
+mod ffd;
+mod ilp;
+mod index;
+mod item;
+mod knapsack;
+mod local_search;
+mod multistart;
+mod options;
+mod ordering;
+mod packer;
+mod preempt;
+mod problem;
+mod repack;
+mod resource;
+mod rng;
+mod result;
+mod strategy;
+mod sweep;
+mod topology;
+
+pub use anneal::{anneal, AnnealOptions};
+pub use bin::{Bin, CapacityExceeded};
+pub use bin_type::BinType;
+pub use bounds::{lower_bounds, LowerBounds};
+pub use budget::Budget;
+pub use constraint::Constraint;
+pub use error::PackError;
+pub use exact::{solve_exact, ExactSolution};
+pub use ffd::{bin_packing_weighted_ffd, pack};
+pub use ilp::IlpModel;
+pub use item::Item;
+pub use knapsack::{pack_fixed, FixedPacking, Maximise};
+pub use local_search::improve;
+pub use multistart::{multi_start, MultiStartOptions, MultiStartResult, Objective, RunScore, ScoreFn};
+pub use options::{CapacityMode, Overcommit, PackOptions};
+pub use ordering::{ItemOrdering, SortKey};
+pub use packer::Packer;
+pub use preempt::{preempt, Eviction, PreemptionPlan};
+pub use problem::Problem;
+pub use repack::{repack, Move, MoveCost, RepackOptions, RepackPlan};
+pub use resource::ResourceSchema;
+pub use result::{PackResult, Unplaced, UnplacedReason};
+pub use strategy::{AlmostWorstFit, BestFit, FirstFit, NextFit, PlacementContext, PlacementStrategy, WorstFit};
+pub use sweep::{sweep, SweepPoint, SweepReport};
+pub use topology::{SpreadReport, Topology};
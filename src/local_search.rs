@@ -0,0 +1,223 @@
+use crate::bin::Bin;
+use crate::budget::{Budget, Tracker};
+use crate::constraint::ConstraintSet;
+use crate::error::PackError;
+use crate::ffd;
+use crate::item::Item;
+use crate::options::PackOptions;
+use crate::ordering;
+use crate::problem::Problem;
+use crate::result::PackResult;
+
+/// Improves a packing of `problem` by trying to empty its least-full bins.
+///
+/// Bins opened by the solver are visited from least to most full. Each item
+/// of the visited bin is, in order of preference:
+///
+/// - moved to another bin it fits in (1-move),
+/// - swapped with a smaller item of another bin (1-1 swap), or
+/// - swapped, together with a second item of the visited bin, for a single
+///   smaller item of another bin (2-1 swap).
+///
+/// Every move respects capacities and the problem's constraints and leaves
+/// less in the visited bin, and a bin that ends up empty is dropped. Items
+/// that must share a bin with others or are spread over the topology are
+/// never moved. Existing bins are never emptied and their items never moved,
+/// but they accept moved items. The search stops
+/// when a pass over all bins empties none, or when `budget`, counting tried
+/// moves as iterations, runs out. Bins are then switched to the cheapest type
+/// that holds their items.
+///
+/// `result` may come from [`pack`](crate::pack) with any strategy and
+/// ordering, or from any other solver for `problem`.
+///
+/// ```
+/// use bin_packer::{improve, pack, Budget, Item, PackOptions, Problem, ResourceSchema};
+///
+/// let items = [[3, 24], [5, 8], [6, 32], [5, 56]].into_iter().enumerate().map(|(i, demand)| Item::new(format!("job-{}", i), demand));
+/// let problem = Problem::new(ResourceSchema::new(["cores", "memory_gb"]), [10, 80]).with_items(items);
+/// let options = PackOptions::new([0.5, 0.5]);
+/// let packed = pack(&problem, &options)?;
+/// assert_eq!(packed.bins_used(), 3);
+/// let improved = improve(&problem, packed, &options, &Budget::iterations(1_000))?;
+/// assert_eq!(improved.bins_used(), 2);
+/// # Ok::<(), bin_packer::PackError>(())
+/// ```
+pub fn improve(
+    problem: &Problem,
+    result: PackResult,
+    options: &PackOptions,
+    budget: &Budget,
+) -> Result<PackResult, PackError> {
+    problem.validate()?;
+    let options = options.normalized(&problem.schema)?;
+    let constraints = ConstraintSet::new(&problem.constraints, &problem.topology)?;
+    Ok(run(problem, result, &options, &constraints, budget))
+}
+
+/// [`improve`] for already validated input and normalised options.
+pub(crate) fn run(
+    problem: &Problem,
+    mut result: PackResult,
+    options: &PackOptions,
+    constraints: &ConstraintSet,
+    budget: &Budget,
+) -> PackResult {
+    let mut search = Search {
+        reference: problem.reference_capacity(),
+        options,
+        constraints,
+        existing_bins: result.existing_bins,
+        tracker: budget.start(),
+    };
+    let bins = &mut result.bins;
+
+    loop {
+        let mut by_load: Vec<(f64, usize)> =
+            (search.existing_bins..bins.len()).map(|b| (search.load(&bins[b]), b)).collect();
+        by_load.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let emptied = by_load.into_iter().map(|(_, b)| b).find(|&b| search.try_empty(bins, b));
+        match emptied {
+            Some(b) => {
+                bins.remove(b);
+            }
+            None => break,
+        }
+        if search.tracker.exhausted() {
+            break;
+        }
+    }
+
+    ffd::downsize(&mut result.bins[result.existing_bins..], problem, options);
+    result.cost = ffd::total_cost(&result.bins[result.existing_bins..], problem);
+    result
+}
+
+struct Search<'a> {
+    reference: Vec<u32>,
+    options: &'a PackOptions,
+    constraints: &'a ConstraintSet,
+    existing_bins: usize,
+    tracker: Tracker,
+}
+
+impl Search<'_> {
+    fn size(&self, item: &Item) -> f64 {
+        ordering::weighted_sum(&ordering::normalize(item, &self.reference), &self.options.weights)
+    }
+
+    fn load(&self, bin: &Bin) -> f64 {
+        bin.items().iter().map(|item| self.size(item)).sum()
+    }
+
+    // Moves items out of bin `b` until it is empty or no move applies.
+    fn try_empty(&mut self, bins: &mut [Bin], b: usize) -> bool {
+        if bins[b].items().iter().any(|item| self.constraints.is_pinned(&item.id)) {
+            return false;
+        }
+        while !bins[b].items().is_empty() {
+            let mut order: Vec<usize> = (0..bins[b].items().len()).collect();
+            order.sort_by(|&x, &y| self.size(&bins[b].items()[y]).total_cmp(&self.size(&bins[b].items()[x])));
+
+            let moved = order
+                .into_iter()
+                .any(|i| self.relocate(bins, b, i) || self.swap_one(bins, b, i) || self.swap_two(bins, b, i));
+            if !moved || self.tracker.exhausted() {
+                break;
+            }
+        }
+        bins[b].items().is_empty()
+    }
+
+    // 1-move: item `i` of bin `b` into the fullest other bin it fits in.
+    fn relocate(&mut self, bins: &mut [Bin], b: usize, i: usize) -> bool {
+        let item = &bins[b].items()[i];
+        let mut target: Option<(f64, usize)> = None;
+        for c in (0..bins.len()).filter(|&c| c != b) {
+            if !self.tracker.tick() {
+                return false;
+            }
+            if bins[c].fits(item, self.options) && self.constraints.admits(&bins[c], &[item]) {
+                let load = self.load(&bins[c]);
+                if target.is_none_or(|(best, _)| load > best) {
+                    target = Some((load, c));
+                }
+            }
+        }
+        let Some((_, c)) = target else { return false };
+        let item = bins[b].remove_item(i);
+        bins[c].push_item(item);
+        true
+    }
+
+    // 1-1 swap: item `i` of bin `b` for a smaller item of another new bin.
+    fn swap_one(&mut self, bins: &mut [Bin], b: usize, i: usize) -> bool {
+        let size = self.size(&bins[b].items()[i]);
+        for c in (self.existing_bins..bins.len()).filter(|&c| c != b) {
+            for j in 0..bins[c].items().len() {
+                if !self.tracker.tick() {
+                    return false;
+                }
+                if self.size(&bins[c].items()[j]) >= size {
+                    continue;
+                }
+                if self.exchange_fits(&bins[b], &[i], &bins[c], &[j]) {
+                    let incoming = bins[c].remove_item(j);
+                    let outgoing = bins[b].replace_item(i, incoming);
+                    bins[c].push_item(outgoing);
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    // 2-1 swap: item `i` and another item of bin `b` for a single item of
+    // another new bin that is smaller than the two together.
+    fn swap_two(&mut self, bins: &mut [Bin], b: usize, i: usize) -> bool {
+        let size = self.size(&bins[b].items()[i]);
+        for k in (0..bins[b].items().len()).filter(|&k| k != i) {
+            let pair = size + self.size(&bins[b].items()[k]);
+            for c in (self.existing_bins..bins.len()).filter(|&c| c != b) {
+                for j in 0..bins[c].items().len() {
+                    if !self.tracker.tick() {
+                        return false;
+                    }
+                    if self.size(&bins[c].items()[j]) >= pair {
+                        continue;
+                    }
+                    if self.exchange_fits(&bins[b], &[i, k], &bins[c], &[j]) {
+                        let incoming = bins[c].remove_item(j);
+                        let (first, second) = (i.max(k), i.min(k));
+                        let a = bins[b].remove_item(first);
+                        let z = bins[b].remove_item(second);
+                        bins[b].push_item(incoming);
+                        bins[c].push_item(a);
+                        bins[c].push_item(z);
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    // Whether bins `from` and `to` both still fit once the items at `out` in
+    // `from` and those at `back` in `to` trade places.
+    fn exchange_fits(&self, from: &Bin, out: &[usize], to: &Bin, back: &[usize]) -> bool {
+        let traded = |bin: &Bin, leaving: &[usize], arriving: &Bin, taken: &[usize]| {
+            let mut after = bin.clone();
+            let mut leaving = leaving.to_vec();
+            leaving.sort_unstable();
+            for &x in leaving.iter().rev() {
+                after.remove_item(x);
+            }
+            let incoming: Vec<&Item> = taken.iter().map(|&x| &arriving.items()[x]).collect();
+            incoming.iter().all(|item| !self.constraints.is_pinned(&item.id))
+                && self.constraints.admits(&after, &incoming)
+                && incoming.iter().all(|&item| after.add_item(item.clone(), self.options))
+        };
+        traded(to, back, from, out) && traded(from, out, to, back)
+    }
+}
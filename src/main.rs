@@ -1,92 +1,38 @@
-use std::collections::VecDeque;
+use bin_packer::{
+    bin_packing_weighted_ffd, multi_start, sweep, AlmostWorstFit, BestFit, FirstFit, Item, ItemOrdering, MultiStartOptions, PackError,
+    PackOptions, Problem, ResourceSchema, WorstFit,
+};
 
-// Weighted First-Fit Decreasing (FFD) heuristic
-// https://en.wikipedia.org/wiki/First-fit_bin_packing
-
-
-
-
-#[derive(Debug)]
-struct Bin {
-    core_capacity: u32,
-    disk_capacity: u32,
-    items: Vec<(u32, u32)>, // Each item is (cores, disk space)
-}
-
-impl Bin {
-    fn new(core_capacity: u32, disk_capacity: u32) -> Self {
-        Bin {
-            core_capacity,
-            disk_capacity,
-            items: Vec::new(),
-        }
-    }
-
-    fn add_item(&mut self, cores: u32, disk: u32, core_weight: f32, disk_weight: f32) -> bool {
-        let core_remaining = self.remaining_core_capacity() as f32;
-        let disk_remaining = self.remaining_disk_capacity() as f32;
-        let core_needed = cores as f32;
-        let disk_needed = disk as f32;
-
-        // Check if adding the item fits within weighted capacities.
-        if core_remaining >= core_needed * core_weight && disk_remaining >= disk_needed * disk_weight {
-            self.items.push((cores, disk));
-            true
-        } else {
-            false
-        }
-    }
-
-    fn remaining_core_capacity(&self) -> u32 {
-        self.core_capacity.saturating_sub(self.items.iter().map(|(c, _)| c).sum::<u32>())
-    }
-
-    fn remaining_disk_capacity(&self) -> u32 {
-        self.disk_capacity.saturating_sub(self.items.iter().map(|(_, d)| d).sum::<u32>())
-    }
-}
-
-
-fn bin_packing_weighted_ffd(
-    items: Vec<(u32, u32)>,
+fn print_packing(
+    items: &[Item],
     core_capacity: u32,
     disk_capacity: u32,
     core_weight: f32,
     disk_weight: f32,
-) -> Vec<Bin> {
-    assert!((core_weight + disk_weight - 1.0).abs() == 0.0, "Weights must sum to 1.0");
-
-    let mut sorted_items = items.clone();
-    sorted_items.sort_unstable_by(|a, b| {
-        let a_value = (a.0 as f32) * core_weight + (a.1 as f32) * disk_weight;
-        let b_value = (b.0 as f32) * core_weight + (b.1 as f32) * disk_weight;
-        b_value.partial_cmp(&a_value).unwrap()
-    });
-
-    let mut bins: Vec<Bin> = Vec::new();
-
-    for (cores, disk) in sorted_items {
-        let mut placed = false;
-        for bin in &mut bins {
-            if bin.add_item(cores, disk, core_weight, disk_weight) {
-                placed = true;
-                break;
-            }
-        }
-        if !placed {
-            // Create a new bin if the item didn't fit in any existing bin.
-            // Naive for sould be good for now
-            let mut new_bin = Bin::new(core_capacity, disk_capacity);
-            new_bin.add_item(cores, disk, core_weight, disk_weight);
-            bins.push(new_bin);
-        }
+) -> Result<(), PackError> {
+    println!("\ncore_weight: {}\ndisk_weight: {}\n", core_weight, disk_weight);
+    let result = bin_packing_weighted_ffd(items, core_capacity, disk_capacity, core_weight, disk_weight)?;
+    for (i, bin) in result.bins.iter().enumerate() {
+        let items: Vec<String> = bin.items().iter().map(|item| format!("{} {:?}", item.id, item.demand)).collect();
+        println!(
+            "Bin {}: {:?}, Remaining Cores: {}, Remaining Disk: {}",
+            i + 1,
+            items,
+            bin.remaining(0),
+            bin.remaining(1)
+        );
     }
-
-    bins
+    // The legacy fit check can fill a bin past its capacity, so there is no
+    // lower bound to compare against.
+    println!("Bins: {}", result.bins_used());
+    for unplaced in &result.unplaced {
+        println!("Unplaced: {}", unplaced);
+    }
+    Ok(())
 }
 
-fn main() {
-    let items = vec![
+fn main() -> Result<(), PackError> {
+    let items: Vec<Item> = [
         (4, 100), // 4 cores, 100 GB
         (2, 50),
         (6, 150),
@@ -95,37 +41,54 @@ fn main() {
         (5, 120),
         (2, 60),
         (4, 90),
-    ];
+        (30, 600), // Larger than any bin, reported as unplaced.
+    ]
+    .into_iter()
+    .enumerate()
+    .map(|(i, (cores, disk))| Item::new(format!("job-{}", i + 1), [cores, disk]))
+    .collect();
     let core_capacity = 10; // Each server/bin has 10 cores.
     let disk_capacity = 200; // Each server/bin has 200 GB of disk space.
 
-
-    let core_weight = 0.6; // 60% priority to core usage.
-    let disk_weight = 0.4; // 40% priority to disk usage.
-
-    println!("\ncore_weight: {}\ndisk_weight: {}\n", core_weight, disk_weight);
-    let bins = bin_packing_weighted_ffd(items.clone(), core_capacity, disk_capacity, core_weight, disk_weight);
-    for (i, bin) in bins.iter().enumerate() {
-        println!(
-            "Bin {}: {:?}, Remaining Cores: {}, Remaining Disk: {}",
-            i + 1,
-            bin.items,
-            bin.remaining_core_capacity(),
-            bin.remaining_disk_capacity()
-        );
-    }
-
-    let core_weight = 0.2; // 20% priority to core usage.
-    let disk_weight = 0.8; // 80% priority to disk usage.
-    println!("\ncore_weight: {}\ndisk_weight: {}\n", core_weight, disk_weight);
-    let bins = bin_packing_weighted_ffd(items, core_capacity, disk_capacity, core_weight, disk_weight);
-    for (i, bin) in bins.iter().enumerate() {
+    // 60% priority to core usage, 40% to disk usage.
+    print_packing(&items, core_capacity, disk_capacity, 0.6, 0.4)?;
+    // 20% priority to core usage, 80% to disk usage.
+    print_packing(&items, core_capacity, disk_capacity, 0.2, 0.8)?;
+
+    // Rather than comparing weights by eye, try a grid of them with several
+    // orderings and strategies and keep the packing with the fewest bins.
+    let problem = Problem::new(ResourceSchema::new(["cores", "disk_gb"]), [core_capacity, disk_capacity]).with_items(items);
+    let search = MultiStartOptions::default()
+        .with_weight_grid(2, 10)
+        .with_orderings([ItemOrdering::WeightedSum, ItemOrdering::MaxDimension, ItemOrdering::L2Norm])
+        .with_strategy(FirstFit)
+        .with_strategy(BestFit)
+        .with_strategy(WorstFit)
+        .with_strategy(AlmostWorstFit);
+    let outcome = multi_start(&problem, &PackOptions::new([0.5, 0.5]), &search)?;
+    let best = &outcome.runs[outcome.best_run];
+    println!(
+        "\nMulti-start: {} configurations, best {} bins with weights {:?}, {:?}, {:?}",
+        outcome.runs.len(),
+        best.bins_used,
+        best.options.weights,
+        best.options.ordering,
+        best.options.strategy
+    );
+
+    // The weight trade-offs worth considering, with the capacity each leaves
+    // stranded.
+    let report = sweep(&problem, &PackOptions::new([0.5, 0.5]), &MultiStartOptions::default().with_weight_grid(2, 10))?;
+    for point in report.pareto_front() {
         println!(
-            "Bin {}: {:?}, Remaining Cores: {}, Remaining Disk: {}",
-            i + 1,
-            bin.items,
-            bin.remaining_core_capacity(),
-            bin.remaining_disk_capacity()
+            "Pareto: weights {:?}, {} bins, utilisation {:.0}% cores {:.0}% disk, stranded {:.0}% cores {:.0}% disk",
+            point.options.weights,
+            point.bins_used,
+            point.utilisation[0] * 100.0,
+            point.utilisation[1] * 100.0,
+            point.stranded[0] * 100.0,
+            point.stranded[1] * 100.0
         );
     }
+    Ok(())
 }
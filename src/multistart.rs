@@ -0,0 +1,297 @@
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use crate::error::PackError;
+use crate::ffd::pack;
+use crate::options::PackOptions;
+use crate::ordering::ItemOrdering;
+use crate::problem::Problem;
+use crate::result::PackResult;
+use crate::strategy::PlacementStrategy;
+
+/// A user-supplied objective: the score of a packing, lower is better.
+pub type ScoreFn = Arc<dyn Fn(&PackResult) -> f64 + Send + Sync>;
+
+/// What [`multi_start`] minimises when comparing packings. Packings that
+/// leave fewer items unplaced always win; the objective decides between the
+/// rest.
+#[derive(Clone, Default)]
+pub enum Objective {
+    /// Fewest bins used, then lowest cost.
+    #[default]
+    Bins,
+    /// Lowest cost of new bins, then fewest bins used.
+    Cost,
+    /// A caller-supplied score.
+    Custom(ScoreFn),
+}
+
+impl Objective {
+    pub fn custom(score: impl Fn(&PackResult) -> f64 + Send + Sync + 'static) -> Self {
+        Objective::Custom(Arc::new(score))
+    }
+
+    // The objective's score of `result`, then the score ties are broken by.
+    fn scores(&self, result: &PackResult) -> (f64, f64) {
+        match self {
+            Objective::Bins => (result.bins_used() as f64, result.cost),
+            Objective::Cost => (result.cost, result.bins_used() as f64),
+            Objective::Custom(score) => (score(result), 0.0),
+        }
+    }
+}
+
+impl fmt::Debug for Objective {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Objective::Bins => f.write_str("Bins"),
+            Objective::Cost => f.write_str("Cost"),
+            Objective::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+/// The configurations [`multi_start`] tries: every combination of the listed
+/// weights, orderings, strategies and tie-break seeds. An empty list keeps
+/// the setting of the base [`PackOptions`].
+#[derive(Debug, Clone, Default)]
+pub struct MultiStartOptions {
+    pub weights: Vec<Vec<f32>>,
+    pub orderings: Vec<ItemOrdering>,
+    pub strategies: Vec<Arc<dyn PlacementStrategy>>,
+    /// Seeds for [`PackOptions::tie_break_seed`].
+    pub seeds: Vec<u64>,
+    pub objective: Objective,
+    /// Number of worker threads; 0 uses the available parallelism.
+    pub threads: usize,
+}
+
+impl MultiStartOptions {
+    pub fn with_weights<W: Into<Vec<f32>>>(mut self, weights: impl IntoIterator<Item = W>) -> Self {
+        self.weights.extend(weights.into_iter().map(Into::into));
+        self
+    }
+
+    /// Adds every weight vector over `dimensions` whose weights are
+    /// multiples of `1 / steps` summing to 1, e.g. (0, 1), (0.5, 0.5) and
+    /// (1, 0) for two dimensions and two steps.
+    pub fn with_weight_grid(mut self, dimensions: usize, steps: usize) -> Self {
+        let steps = steps.max(1);
+        let mut weights = vec![0; dimensions];
+        grid(&mut weights, 0, steps, &mut |units| {
+            self.weights.push(units.iter().map(|&u| u as f32 / steps as f32).collect());
+        });
+        self
+    }
+
+    pub fn with_orderings(mut self, orderings: impl IntoIterator<Item = ItemOrdering>) -> Self {
+        self.orderings.extend(orderings);
+        self
+    }
+
+    pub fn with_strategy(mut self, strategy: impl PlacementStrategy + 'static) -> Self {
+        self.strategies.push(Arc::new(strategy));
+        self
+    }
+
+    pub fn with_seeds(mut self, seeds: impl IntoIterator<Item = u64>) -> Self {
+        self.seeds.extend(seeds);
+        self
+    }
+
+    pub fn with_objective(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    // `base` with each combination of the listed settings, weights varying
+    // slowest and seeds fastest.
+    pub(crate) fn configurations(&self, base: &PackOptions) -> Vec<PackOptions> {
+        let weights: Vec<&[f32]> = if self.weights.is_empty() {
+            vec![&base.weights]
+        } else {
+            self.weights.iter().map(Vec::as_slice).collect()
+        };
+        let orderings = if self.orderings.is_empty() { std::slice::from_ref(&base.ordering) } else { &self.orderings };
+        let strategies = if self.strategies.is_empty() { std::slice::from_ref(&base.strategy) } else { &self.strategies };
+        let seeds: Vec<Option<u64>> = if self.seeds.is_empty() {
+            vec![base.tie_break_seed]
+        } else {
+            self.seeds.iter().copied().map(Some).collect()
+        };
+
+        let mut configurations = Vec::with_capacity(weights.len() * orderings.len() * strategies.len() * seeds.len());
+        for &weights in &weights {
+            for ordering in orderings {
+                for strategy in strategies {
+                    for &tie_break_seed in &seeds {
+                        configurations.push(PackOptions {
+                            weights: weights.to_vec(),
+                            ordering: ordering.clone(),
+                            strategy: strategy.clone(),
+                            tie_break_seed,
+                            ..base.clone()
+                        });
+                    }
+                }
+            }
+        }
+        configurations
+    }
+}
+
+// Calls `visit` with every way of sharing `left` units among the entries of
+// `units` from `d` on.
+fn grid(units: &mut [usize], d: usize, left: usize, visit: &mut impl FnMut(&[usize])) {
+    if d + 1 >= units.len() {
+        if let Some(last) = units.get_mut(d) {
+            *last = left;
+            visit(units);
+        }
+        return;
+    }
+    for u in (0..=left).rev() {
+        units[d] = u;
+        grid(units, d + 1, left - u, visit);
+    }
+}
+
+/// How one configuration of a [`multi_start`] run did.
+#[derive(Debug, Clone)]
+pub struct RunScore {
+    /// The options the configuration packed with.
+    pub options: PackOptions,
+    pub bins_used: usize,
+    pub cost: f64,
+    /// Number of items left unplaced.
+    pub unplaced: usize,
+    /// The packing's score under the objective; lower is better.
+    pub score: f64,
+}
+
+/// Outcome of [`multi_start`].
+#[derive(Debug, Clone)]
+pub struct MultiStartResult {
+    /// The best packing found.
+    pub best: PackResult,
+    /// Index into `runs` of the configuration that found `best`.
+    pub best_run: usize,
+    /// Every configuration tried, in the order they were generated.
+    pub runs: Vec<RunScore>,
+}
+
+/// Packs `problem` with every configuration of `multi_start`, spread over
+/// several threads, and returns the best packing under its objective along
+/// with the score of each configuration.
+///
+/// Settings not varied by `multi_start`, such as the capacity mode and local
+/// search, are taken from `options`. Ties go to the configuration generated
+/// first, so the result does not depend on the number of threads.
+///
+/// Fails with the error of the first configuration that fails, e.g. because
+/// its weights do not match the schema.
+///
+/// ```
+/// use bin_packer::{multi_start, BestFit, FirstFit, Item, MultiStartOptions, PackOptions, Problem, ResourceSchema};
+///
+/// let items = (0..20).map(|i| Item::new(format!("job-{}", i), [1 + i % 7, 10 + 13 * (i % 5)]));
+/// let problem = Problem::new(ResourceSchema::new(["cores", "disk_gb"]), [10, 200]).with_items(items);
+/// let search = MultiStartOptions::default()
+///     .with_weight_grid(2, 4)
+///     .with_strategy(FirstFit)
+///     .with_strategy(BestFit)
+///     .with_seeds(0..3);
+/// let outcome = multi_start(&problem, &PackOptions::new([0.5, 0.5]), &search)?;
+/// assert_eq!(outcome.runs.len(), 5 * 2 * 3);
+/// assert!(outcome.runs.iter().all(|run| run.bins_used >= outcome.best.bins_used()));
+/// # Ok::<(), bin_packer::PackError>(())
+/// ```
+pub fn multi_start(problem: &Problem, options: &PackOptions, multi_start: &MultiStartOptions) -> Result<MultiStartResult, PackError> {
+    let configurations = multi_start.configurations(options);
+    // Each worker keeps only the best packing it has seen.
+    let init = || (Vec::new(), None::<((usize, f64, f64), usize, PackResult)>);
+    let outcomes = in_parallel(configurations.len(), multi_start.threads, init, |(scores, best), run| {
+        let configuration = &configurations[run];
+        let result = match pack(problem, configuration) {
+            Ok(result) => result,
+            Err(error) => {
+                scores.push((run, Err(error)));
+                return;
+            }
+        };
+        let (score, tie_break) = multi_start.objective.scores(&result);
+        let key = (result.unplaced.len(), score, tie_break);
+        scores.push((
+            run,
+            Ok(RunScore {
+                options: configuration.clone(),
+                bins_used: result.bins_used(),
+                cost: result.cost,
+                unplaced: result.unplaced.len(),
+                score,
+            }),
+        ));
+        if best.as_ref().is_none_or(|(best_key, best_run, _)| better(key, run, *best_key, *best_run)) {
+            *best = Some((key, run, result));
+        }
+    });
+
+    let mut scores: Vec<(usize, Result<RunScore, PackError>)> = Vec::with_capacity(configurations.len());
+    let mut best: Option<((usize, f64, f64), usize, PackResult)> = None;
+    for (worker_scores, worker_best) in outcomes {
+        scores.extend(worker_scores);
+        if let Some((key, run, result)) = worker_best {
+            if best.as_ref().is_none_or(|(best_key, best_run, _)| better(key, run, *best_key, *best_run)) {
+                best = Some((key, run, result));
+            }
+        }
+    }
+    scores.sort_by_key(|(run, _)| *run);
+    let runs = scores.into_iter().map(|(_, score)| score).collect::<Result<Vec<_>, _>>()?;
+    let (_, best_run, best) = best.expect("every configuration succeeded and there is at least one");
+    Ok(MultiStartResult { best, best_run, runs })
+}
+
+// Whether the run with `key` beats the one with `other_key`; ties go to the
+// earlier run.
+fn better(key: (usize, f64, f64), run: usize, other_key: (usize, f64, f64), other_run: usize) -> bool {
+    key.0
+        .cmp(&other_key.0)
+        .then(key.1.total_cmp(&other_key.1))
+        .then(key.2.total_cmp(&other_key.2))
+        .then(run.cmp(&other_run))
+        .is_lt()
+}
+
+// Calls `run` with each of `0..jobs` across `threads` worker threads, or as
+// many as are available if 0. Each worker takes the next job until none are
+// left and records what it finds in a state created by `init`; the states
+// are returned in no particular order.
+pub(crate) fn in_parallel<S: Send>(jobs: usize, threads: usize, init: impl Fn() -> S + Sync, run: impl Fn(&mut S, usize) + Sync) -> Vec<S> {
+    let threads = match threads {
+        0 => thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    };
+    let next = AtomicUsize::new(0);
+    let worker = || {
+        let mut state = init();
+        loop {
+            let job = next.fetch_add(1, Ordering::Relaxed);
+            if job >= jobs {
+                return state;
+            }
+            run(&mut state, job);
+        }
+    };
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads.min(jobs).max(1)).map(|_| scope.spawn(worker)).collect();
+        handles.into_iter().map(|handle| handle.join().expect("worker thread panicked")).collect()
+    })
+}
@@ -0,0 +1,178 @@
+use std::sync::Arc;
+
+use crate::budget::Budget;
+use crate::error::PackError;
+use crate::ordering::ItemOrdering;
+use crate::resource::ResourceSchema;
+use crate::strategy::{FirstFit, PlacementStrategy};
+
+/// How a bin decides whether an item fits into its remaining capacity.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CapacityMode {
+    /// Accept an item when `remaining >= needed * weight` in each dimension.
+    ///
+    /// This is the original behaviour of [`bin_packing_weighted_ffd`]: weights
+    /// below 1.0 shrink the demand that is checked, so bins can end up
+    /// overcommitted.
+    ///
+    /// [`bin_packing_weighted_ffd`]: crate::bin_packing_weighted_ffd
+    Weighted,
+    /// Accept an item only when `needed <= remaining` in every dimension.
+    ///
+    /// Weights only influence ordering. Capacities are scaled by the given
+    /// overcommit ratios before the check, so a ratio of 1.0 never
+    /// overcommits.
+    Strict(Overcommit),
+}
+
+/// Per-dimension factors applied to a bin's capacity in
+/// [`CapacityMode::Strict`]. A ratio of 1.5 lets a 10-core bin hold 15 cores
+/// worth of items.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Overcommit {
+    /// Ratio per dimension, in schema order. Empty means 1.0 everywhere.
+    pub ratios: Vec<f32>,
+}
+
+impl Overcommit {
+    pub fn new(ratios: impl Into<Vec<f32>>) -> Self {
+        Overcommit { ratios: ratios.into() }
+    }
+
+    /// Ratio applied to dimension `d`.
+    pub fn ratio(&self, d: usize) -> f32 {
+        self.ratios.get(d).copied().unwrap_or(1.0)
+    }
+}
+
+/// Settings for a packing run.
+///
+/// Weights need not sum to 1.0; they are normalised before packing.
+#[derive(Debug, Clone)]
+pub struct PackOptions {
+    /// Weight per dimension, in schema order, used by
+    /// [`ItemOrdering::WeightedSum`] and to score bins by remaining capacity.
+    pub weights: Vec<f32>,
+    pub capacity_mode: CapacityMode,
+    /// Order in which items are packed.
+    pub ordering: ItemOrdering,
+    /// Picks the bin for each item; [`FirstFit`] by default.
+    pub strategy: Arc<dyn PlacementStrategy>,
+    /// Run [`improve`](crate::improve) on the packing with this budget.
+    pub local_search: Option<Budget>,
+    /// Fail with [`PackError::OversizedItem`] instead of reporting items
+    /// that fit in no bin as unplaced.
+    pub fail_on_oversized: bool,
+    /// Order items with equal sort keys randomly, from this seed, rather
+    /// than as given.
+    pub tie_break_seed: Option<u64>,
+}
+
+impl PackOptions {
+    /// Strict packing with the given ordering weights and no overcommit.
+    pub fn new(weights: impl Into<Vec<f32>>) -> Self {
+        PackOptions {
+            weights: weights.into(),
+            capacity_mode: CapacityMode::Strict(Overcommit::default()),
+            ordering: ItemOrdering::default(),
+            strategy: Arc::new(FirstFit),
+            local_search: None,
+            fail_on_oversized: false,
+            tie_break_seed: None,
+        }
+    }
+
+    /// Packing with the legacy [`CapacityMode::Weighted`] fit check.
+    pub fn weighted(weights: impl Into<Vec<f32>>) -> Self {
+        PackOptions {
+            capacity_mode: CapacityMode::Weighted,
+            ..PackOptions::new(weights)
+        }
+    }
+
+    /// Switches to strict packing that oversubscribes each dimension by the
+    /// given ratio.
+    pub fn with_overcommit(mut self, ratios: impl Into<Vec<f32>>) -> Self {
+        self.capacity_mode = CapacityMode::Strict(Overcommit::new(ratios));
+        self
+    }
+
+    pub fn with_capacity_mode(mut self, capacity_mode: CapacityMode) -> Self {
+        self.capacity_mode = capacity_mode;
+        self
+    }
+
+    pub fn with_ordering(mut self, ordering: ItemOrdering) -> Self {
+        self.ordering = ordering;
+        self
+    }
+
+    pub fn with_strategy(mut self, strategy: impl PlacementStrategy + 'static) -> Self {
+        self.strategy = Arc::new(strategy);
+        self
+    }
+
+    /// Follows the constructive packing with a local-search pass limited by
+    /// `budget`.
+    pub fn with_local_search(mut self, budget: Budget) -> Self {
+        self.local_search = Some(budget);
+        self
+    }
+
+    /// Makes an item that fits in no bin an error rather than an
+    /// [`Unplaced`](crate::Unplaced) entry.
+    pub fn fail_on_oversized(mut self) -> Self {
+        self.fail_on_oversized = true;
+        self
+    }
+
+    /// Breaks ties between items with equal sort keys in a random order
+    /// drawn from `seed`.
+    pub fn with_tie_break_seed(mut self, seed: u64) -> Self {
+        self.tie_break_seed = Some(seed);
+        self
+    }
+
+    /// Checks the weights and overcommit ratios against `schema` and returns a
+    /// copy whose weights sum to 1.0.
+    pub(crate) fn normalized(&self, schema: &ResourceSchema) -> Result<PackOptions, PackError> {
+        if self.weights.len() != schema.len() {
+            return Err(PackError::DimensionMismatch {
+                what: "weights".to_string(),
+                expected: schema.len(),
+                found: self.weights.len(),
+            });
+        }
+        for (d, &weight) in self.weights.iter().enumerate() {
+            if !weight.is_finite() || weight < 0.0 {
+                let dimension = schema.name(d).to_string();
+                return Err(PackError::InvalidWeight { dimension, weight });
+            }
+        }
+        let total: f32 = self.weights.iter().sum();
+        if total <= 0.0 {
+            return Err(PackError::ZeroWeights);
+        }
+
+        if let CapacityMode::Strict(overcommit) = &self.capacity_mode {
+            if !overcommit.ratios.is_empty() && overcommit.ratios.len() != schema.len() {
+                return Err(PackError::DimensionMismatch {
+                    what: "overcommit ratios".to_string(),
+                    expected: schema.len(),
+                    found: overcommit.ratios.len(),
+                });
+            }
+            for (d, &ratio) in overcommit.ratios.iter().enumerate() {
+                if !ratio.is_finite() || ratio <= 0.0 {
+                    let dimension = schema.name(d).to_string();
+                    return Err(PackError::InvalidOvercommit { dimension, ratio });
+                }
+            }
+        }
+
+        Ok(PackOptions {
+            weights: self.weights.iter().map(|w| w / total).collect(),
+            ..self.clone()
+        })
+    }
+}
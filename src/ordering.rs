@@ -0,0 +1,131 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::item::Item;
+use crate::problem::Problem;
+use crate::rng::Rng;
+
+/// A user-supplied sort key. Receives the item and its demand normalised by
+/// the reference bin capacity; larger keys are packed first.
+pub type SortKey = Arc<dyn Fn(&Item, &[f64]) -> f64 + Send + Sync>;
+
+/// The order in which items are packed, largest key first.
+///
+/// Keys are computed on demands normalised by the reference bin capacity,
+/// the largest capacity per dimension among the bin types and existing bins,
+/// so that e.g. cores and gigabytes are compared on the same scale.
+///
+/// ```
+/// use bin_packer::{pack, Item, ItemOrdering, PackOptions, Problem, ResourceSchema};
+///
+/// // No two items share a bin, so each opens one in packing order.
+/// let items = [Item::new("batch", [15, 8]), Item::new("cache-1", [3, 52]), Item::new("cache-2", [3, 52])];
+/// let problem = Problem::new(ResourceSchema::new(["cores", "memory_gb"]), [16, 64]).with_items(items);
+/// let order = |ordering: ItemOrdering| -> Result<Vec<String>, bin_packer::PackError> {
+///     let result = pack(&problem, &PackOptions::new([0.5, 0.5]).with_ordering(ordering))?;
+///     Ok(result.bins.iter().map(|bin| bin.items()[0].id.clone()).collect())
+/// };
+/// // 15/16 + 8/64 is the largest sum of normalised demands.
+/// assert_eq!(order(ItemOrdering::DotProduct)?, ["batch", "cache-1", "cache-2"]);
+/// // Memory is what the items need most of in total.
+/// assert_eq!(order(ItemOrdering::DemandWeighted)?, ["cache-1", "cache-2", "batch"]);
+/// # Ok::<(), bin_packer::PackError>(())
+/// ```
+#[derive(Clone, Default)]
+pub enum ItemOrdering {
+    /// Sum over dimensions of weight times normalised demand.
+    #[default]
+    WeightedSum,
+    /// Largest normalised demand of any dimension.
+    MaxDimension,
+    /// Euclidean length of the normalised demand vector.
+    L2Norm,
+    /// Product of the normalised demands, i.e. the item's volume.
+    Product,
+    /// Dot product of the normalised demand with the normalised capacity of
+    /// a bin, favouring items that are large in the dimensions bins have
+    /// most of. The largest product over the bin types and existing bins the
+    /// item fits in is used. With a single bin type the normalised capacity
+    /// is 1 in every dimension, so this is the unweighted sum of the
+    /// normalised demands.
+    DotProduct,
+    /// Dot product of the normalised demand with the total normalised demand
+    /// of all items, favouring items that are heavy in the dimensions the
+    /// instance needs most bins for.
+    DemandWeighted,
+    /// A caller-supplied key.
+    Custom(SortKey),
+}
+
+impl ItemOrdering {
+    pub fn custom(key: impl Fn(&Item, &[f64]) -> f64 + Send + Sync + 'static) -> Self {
+        ItemOrdering::Custom(Arc::new(key))
+    }
+
+    /// `items` sorted by decreasing key; equal keys keep their input order,
+    /// or are shuffled by `tie_break_seed` if given.
+    pub(crate) fn sort<'a>(&self, items: &'a [Item], problem: &Problem, weights: &[f32], tie_break_seed: Option<u64>) -> Vec<&'a Item> {
+        let reference = problem.reference_capacity();
+        let normalized: Vec<Vec<f64>> = items.iter().map(|item| normalize(item, &reference)).collect();
+        let totals: Vec<f64> = (0..reference.len()).map(|d| normalized.iter().map(|s| s[d]).sum()).collect();
+        let capacities: Vec<&[u32]> = problem.capacities().collect();
+        // The best aligned capacity among those the item fits in, or among
+        // all if it fits in none.
+        let dot_product = |item: &Item, s: &[f64]| {
+            let dot = |capacity: &&[u32]| s.iter().zip(capacity.iter().zip(&reference)).map(|(x, (&c, &r))| x * c as f64 / r as f64).sum::<f64>();
+            let fitting = capacities.iter().filter(|capacity| item.demand.iter().zip(capacity.iter()).all(|(&q, &c)| q <= c));
+            fitting.map(dot).reduce(f64::max).unwrap_or_else(|| capacities.iter().map(dot).fold(0.0, f64::max))
+        };
+
+        let mut keyed: Vec<(f64, &Item)> = items
+            .iter()
+            .zip(&normalized)
+            .map(|(item, s)| {
+                let key = match self {
+                    ItemOrdering::WeightedSum => weighted_sum(s, weights),
+                    ItemOrdering::MaxDimension => s.iter().copied().fold(0.0, f64::max),
+                    ItemOrdering::L2Norm => s.iter().map(|x| x * x).sum::<f64>().sqrt(),
+                    ItemOrdering::Product => s.iter().product(),
+                    ItemOrdering::DotProduct => dot_product(item, s),
+                    ItemOrdering::DemandWeighted => s.iter().zip(&totals).map(|(x, t)| x * t).sum(),
+                    ItemOrdering::Custom(key) => key(item, s),
+                };
+                (key, item)
+            })
+            .collect();
+        match tie_break_seed {
+            Some(seed) => {
+                let mut rng = Rng::new(seed);
+                let mut tie_broken: Vec<(f64, u64, &Item)> = keyed.into_iter().map(|(key, item)| (key, rng.next_u64(), item)).collect();
+                tie_broken.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+                keyed = tie_broken.into_iter().map(|(key, _, item)| (key, item)).collect();
+            }
+            None => keyed.sort_by(|a, b| b.0.total_cmp(&a.0)),
+        }
+        keyed.into_iter().map(|(_, item)| item).collect()
+    }
+}
+
+impl fmt::Debug for ItemOrdering {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ItemOrdering::WeightedSum => f.write_str("WeightedSum"),
+            ItemOrdering::MaxDimension => f.write_str("MaxDimension"),
+            ItemOrdering::L2Norm => f.write_str("L2Norm"),
+            ItemOrdering::Product => f.write_str("Product"),
+            ItemOrdering::DotProduct => f.write_str("DotProduct"),
+            ItemOrdering::DemandWeighted => f.write_str("DemandWeighted"),
+            ItemOrdering::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+/// Demand of `item` as a fraction of `reference` in each dimension.
+pub(crate) fn normalize(item: &Item, reference: &[u32]) -> Vec<f64> {
+    item.demand.iter().zip(reference).map(|(&q, &c)| q as f64 / c as f64).collect()
+}
+
+/// Sum over dimensions of weight times normalised demand.
+pub(crate) fn weighted_sum(normalized: &[f64], weights: &[f32]) -> f64 {
+    normalized.iter().zip(weights).map(|(&s, &w)| s * w as f64).sum()
+}
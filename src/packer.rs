@@ -0,0 +1,274 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::bin::Bin;
+use crate::bounds;
+use crate::constraint::ConstraintSet;
+use crate::error::PackError;
+use crate::ffd;
+use crate::index::BinIndex;
+use crate::item::Item;
+use crate::options::PackOptions;
+use crate::problem::{self, Problem};
+use crate::result::{PackResult, Unplaced};
+use crate::strategy::PlacementContext;
+
+/// An online packer that places items one at a time as they arrive and
+/// releases them when they leave, without moving anything already placed.
+///
+/// Each inserted item goes into the bin chosen by `options.strategy`, as in
+/// [`pack`](crate::pack), or else into a new bin of the cheapest type it fits
+/// that may still be opened. Bins emptied by [`remove`](Packer::remove) stay
+/// open and are reused until [`close_empty_bins`](Packer::close_empty_bins)
+/// is called, so bin indices returned by [`insert`](Packer::insert) remain
+/// valid until then.
+///
+/// The problem's [`Constraint`](crate::Constraint)s are kept: an item that
+/// must share a bin with one already placed goes into that bin, and no item
+/// goes where an anti-affinity or enforced spread constraint forbids it.
+/// Spread is kept as items arrive; removing items can leave it unbalanced.
+///
+/// ```
+/// use bin_packer::{Item, PackOptions, Packer, Problem, ResourceSchema};
+///
+/// let problem = Problem::new(ResourceSchema::new(["cores", "memory_gb"]), [16, 64]);
+/// let mut packer = Packer::new(&problem, &PackOptions::new([0.5, 0.5]))?;
+/// assert_eq!(packer.insert(Item::new("web", [8, 16]))?, 0);
+/// assert_eq!(packer.insert(Item::new("db", [12, 32]))?, 1);
+/// assert_eq!(packer.insert(Item::new("cache", [4, 32]))?, 0);
+///
+/// packer.remove("db");
+/// assert_eq!(packer.close_empty_bins(), 1);
+/// assert_eq!(packer.result().bins_used(), 1);
+/// # Ok::<(), bin_packer::PackError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Packer {
+    // Schema, catalogue and initial existing bins; `items` is unused.
+    problem: Problem,
+    options: PackOptions,
+    reference: Vec<u32>,
+    empty_bins: Vec<Bin>,
+    bins: Vec<Bin>,
+    index: BinIndex,
+    constraints: ConstraintSet,
+    existing_bins: usize,
+    opened: Vec<usize>,
+    // Bin holding each placed item, keyed by item id.
+    locations: HashMap<String, usize>,
+    current: Option<usize>,
+}
+
+impl Packer {
+    /// A packer over `problem`'s schema, bin types and existing bins, which
+    /// start out holding their items. The problem's `items` are not placed;
+    /// insert them, or start from a batch packing with
+    /// [`from_result`](Packer::from_result).
+    ///
+    /// Fails if the problem is inconsistent with its schema, a capacity is
+    /// zero, a cost is invalid, a constraint is invalid, or the weights or
+    /// overcommit ratios in `options` are invalid.
+    pub fn new(problem: &Problem, options: &PackOptions) -> Result<Self, PackError> {
+        problem.validate_bins()?;
+        let options = options.normalized(&problem.schema)?;
+        let constraints = ConstraintSet::new(&problem.constraints, &problem.topology)?;
+        let problem = Problem {
+            items: Vec::new(),
+            ..problem.clone()
+        };
+        let mut packer = Packer {
+            reference: problem.reference_capacity(),
+            empty_bins: problem.bin_types.iter().enumerate().map(|(t, bt)| Bin::of_type(t, bt)).collect(),
+            bins: problem.existing_bins.clone(),
+            index: BinIndex::new(&problem.existing_bins, problem.schema.len(), &options),
+            constraints,
+            existing_bins: problem.existing_bins.len(),
+            opened: vec![0; problem.bin_types.len()],
+            locations: HashMap::new(),
+            current: None,
+            problem,
+            options,
+        };
+        packer.reindex();
+        Ok(packer)
+    }
+
+    /// A packer that continues from `result`, a packing of `problem` such as
+    /// one returned by [`pack`](crate::pack). Items left unplaced in `result`
+    /// are not retried.
+    pub fn from_result(problem: &Problem, result: PackResult, options: &PackOptions) -> Result<Self, PackError> {
+        let mut packer = Packer::new(problem, options)?;
+        if result.existing_bins != packer.existing_bins {
+            return Err(PackError::DimensionMismatch {
+                what: "result existing bins".to_string(),
+                expected: packer.existing_bins,
+                found: result.existing_bins,
+            });
+        }
+        packer.bins = result.bins;
+        packer.reindex();
+        if packer.locations.len() != packer.bins.iter().map(|bin| bin.items().len()).sum::<usize>() {
+            let mut seen = HashSet::new();
+            let duplicate = packer.bins.iter().flat_map(|bin| bin.items()).find(|item| !seen.insert(&item.id));
+            return Err(PackError::DuplicateItemId(duplicate.map(|item| item.id.clone()).unwrap_or_default()));
+        }
+        Ok(packer)
+    }
+
+    /// Places `item` and returns the index of the bin it went into.
+    ///
+    /// Fails without changing anything if the item does not match the
+    /// schema, its id is already placed, no open bin takes it and it is
+    /// larger than every bin type or requires labels none carries, or no
+    /// open bin has room and no more bins may be opened. Fails with
+    /// [`PackError::Infeasible`] if the item must share a bin that cannot
+    /// take it, or no bin it fits in keeps its spread constraints. Fails with
+    /// [`PackError::InvalidStrategy`] if the strategy selects a bin the item
+    /// cannot go into.
+    pub fn insert(&mut self, item: Item) -> Result<usize, PackError> {
+        problem::check_dimensions(&format!("item {:?} demand", item.id), &item.demand, self.problem.schema.len())?;
+        if self.locations.contains_key(&item.id) {
+            return Err(PackError::DuplicateItemId(item.id));
+        }
+
+        let members = [&item];
+        let partner = self.constraints.group(&item.id).and_then(|group| group.iter().find_map(|id| self.locations.get(id)));
+        let selected = match partner {
+            Some(&b) => {
+                if !self.bins[b].fits(&item, &self.options) || !self.constraints.admits(&self.bins[b], &members) {
+                    return Err(PackError::Infeasible(format!("item {} must join bin {}, which cannot take it", item.id, b)));
+                }
+                Some(b)
+            }
+            None => {
+                let context = PlacementContext::new(&self.bins, &self.index, &item, &members, &self.constraints, &self.options, self.current);
+                context.select(self.options.strategy.as_ref())?
+            }
+        };
+        let b = match selected {
+            Some(b) => b,
+            None => {
+                let fitting: Vec<usize> = (0..self.empty_bins.len()).filter(|&t| self.empty_bins[t].fits(&item, &self.options)).collect();
+                if fitting.is_empty() {
+                    let reason = ffd::oversized(&self.empty_bins, &item, &self.problem, &self.options);
+                    return Err(PackError::OversizedItem(Box::new(Unplaced { item, reason })));
+                }
+                let admitted: Vec<usize> = fitting.into_iter().filter(|&t| self.constraints.admits(&self.empty_bins[t], &members)).collect();
+                if admitted.is_empty() {
+                    return Err(PackError::Infeasible(format!("item {} fits no bin that keeps its spread constraints", item.id)));
+                }
+                // Nothing is known about upcoming items, so the cheapest type
+                // per unit of this item's size wins, among those keeping the
+                // preferred spread constraints if there are any.
+                let available: Vec<usize> = admitted.into_iter().filter(|&t| ffd::has_capacity_left(&self.problem, &self.opened, t)).collect();
+                let keeping: Vec<usize> =
+                    available.iter().copied().filter(|&t| self.constraints.spread_violations(&self.empty_bins[t], &members) == 0).collect();
+                let candidates = if keeping.is_empty() { available } else { keeping };
+                let size = ffd::weighted_size(&item, &self.reference, &self.options);
+                let chosen = ffd::choose_bin_type(candidates.into_iter(), &self.empty_bins, &[&item], &[size], &self.problem, &self.options);
+                let Some(t) = chosen else {
+                    return Err(PackError::NoBinAvailable(item.id));
+                };
+                self.opened[t] += 1;
+                self.bins.push(self.empty_bins[t].clone());
+                self.bins.len() - 1
+            }
+        };
+        self.locations.insert(item.id.clone(), b);
+        self.constraints.place(&self.bins[b], &members);
+        self.bins[b].push_item(item);
+        self.index.update(&self.bins, b, &self.options);
+        self.current = Some(b);
+        Ok(b)
+    }
+
+    /// Takes item `id` out of its bin and returns it, or `None` if no such
+    /// item is placed. Items in existing bins can be removed too.
+    pub fn remove(&mut self, id: &str) -> Option<Item> {
+        let b = self.locations.remove(id)?;
+        let position = self.bins[b].items().iter().position(|item| item.id == id).expect("item is in the bin it was placed in");
+        let item = self.bins[b].remove_item(position);
+        self.constraints.unplace(&self.bins[b], &[&item]);
+        self.index.update(&self.bins, b, &self.options);
+        Some(item)
+    }
+
+    /// Closes the new bins that hold no items and returns how many were
+    /// closed. Bins after a closed one move down to fill its place.
+    pub fn close_empty_bins(&mut self) -> usize {
+        let before = self.bins.len();
+        ffd::drop_empty_new_bins(&mut self.bins, self.existing_bins);
+        self.current = None;
+        self.reindex();
+        before - self.bins.len()
+    }
+
+    /// Existing bins followed by the bins opened so far, including empty
+    /// ones not yet closed.
+    pub fn bins(&self) -> &[Bin] {
+        &self.bins
+    }
+
+    /// Index into [`bins`](Packer::bins) of the bin holding item `id`.
+    pub fn bin_of(&self, id: &str) -> Option<usize> {
+        self.locations.get(id).copied()
+    }
+
+    /// Number of items placed.
+    pub fn len(&self) -> usize {
+        self.locations.len()
+    }
+
+    /// Whether no items are placed.
+    pub fn is_empty(&self) -> bool {
+        self.locations.is_empty()
+    }
+
+    /// Total cost of the open new bins, including empty ones.
+    pub fn cost(&self) -> f64 {
+        ffd::total_cost(&self.bins[self.existing_bins..], &self.problem)
+    }
+
+    /// The current packing, without empty new bins. Its lower bound covers
+    /// every placed item, including those in existing bins.
+    pub fn result(&self) -> PackResult {
+        let mut bins = self.bins.clone();
+        ffd::drop_empty_new_bins(&mut bins, self.existing_bins);
+        let relaxed = Problem {
+            existing_bins: bins[..self.existing_bins]
+                .iter()
+                .map(|bin| {
+                    let mut empty = Bin::with_reserved(bin.capacity.clone(), bin.reserved.clone());
+                    empty.bin_type = bin.bin_type;
+                    empty
+                })
+                .collect(),
+            ..self.problem.clone()
+        };
+        let placed: Vec<&Item> = bins.iter().flat_map(|bin| bin.items()).collect();
+        let lower_bound = bounds::for_items(&placed, &relaxed, &self.options).map(|bounds| bounds.best());
+        PackResult {
+            cost: ffd::total_cost(&bins[self.existing_bins..], &self.problem),
+            bins,
+            existing_bins: self.existing_bins,
+            unplaced: Vec::new(),
+            lower_bound,
+        }
+    }
+
+    // Rebuilds the item locations, opened counts, spread counts and bin
+    // index from `bins`.
+    fn reindex(&mut self) {
+        self.index = BinIndex::new(&self.bins, self.problem.schema.len(), &self.options);
+        self.constraints.track(&self.bins, &self.problem.openable_bins());
+        self.locations = self
+            .bins
+            .iter()
+            .enumerate()
+            .flat_map(|(b, bin)| bin.items().iter().map(move |item| (item.id.clone(), b)))
+            .collect();
+        self.opened.iter_mut().for_each(|count| *count = 0);
+        for t in self.bins[self.existing_bins..].iter().filter_map(|bin| bin.bin_type) {
+            self.opened[t] += 1;
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::bin::Bin;
+use crate::constraint::ConstraintSet;
+use crate::error::PackError;
+use crate::ffd::{total_cost, weighted_size};
+use crate::index::BinIndex;
+use crate::item::Item;
+use crate::options::{CapacityMode, PackOptions};
+use crate::problem::Problem;
+use crate::result::{PackResult, Unplaced, UnplacedReason};
+use crate::strategy::PlacementContext;
+
+/// An item evicted from its bin to make room for one of higher priority.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Eviction {
+    pub item: Item,
+    /// Index of the bin the item was in before any eviction.
+    pub from: usize,
+    /// Id of the item it first made room for.
+    pub preempted_by: String,
+    /// The bin the item ends up in, if another one had room for it. An item
+    /// evicted again from there is moved on or stopped in turn.
+    pub rescheduled_to: Option<usize>,
+}
+
+/// Outcome of [`preempt`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreemptionPlan {
+    /// The existing bins after the evictions and moves, holding the placed
+    /// items. Items that could not be placed even by evicting others are
+    /// reported in its `unplaced`.
+    pub result: PackResult,
+    /// Items to evict, one per item, in the order they were first evicted.
+    pub evictions: Vec<Eviction>,
+}
+
+impl PreemptionPlan {
+    /// The evicted items no bin had room for, which have to stop.
+    pub fn stopped(&self) -> impl Iterator<Item = &Eviction> {
+        self.evictions.iter().filter(|eviction| eviction.rescheduled_to.is_none())
+    }
+}
+
+/// Places `problem`'s items into its existing bins, a fleet of fixed size,
+/// evicting items of lower [`priority`](Item::priority) when there is no
+/// room rather than opening new bins. The bin types are not used.
+///
+/// Items are placed highest priority first, and in the order of
+/// `options.ordering` among equal priorities, each into the bin chosen by
+/// `options.strategy`. An item that fits in no bin goes where room can be
+/// made by evicting only items of lower priority, choosing the bin whose
+/// highest-priority victim is lowest, then with the fewest victims, then
+/// with the least evicted weighted size. Every victim is needed: the item
+/// would not fit were any of them kept. Evicted items move to another bin
+/// that has room for them if there is one, without evicting anything in
+/// turn, and are stopped otherwise. Items that do not fit even by evicting
+/// every lower-priority item are left unplaced.
+///
+/// Fails if the problem is empty or inconsistent with its schema, the
+/// weights or overcommit ratios in `options` are invalid, or the problem
+/// has constraints, which are not supported.
+///
+/// ```
+/// use bin_packer::{preempt, Bin, Item, PackOptions, Problem, ResourceSchema};
+///
+/// let fleet = [
+///     Bin::new([16, 64]).with_items([Item::new("batch-1", [8, 32]), Item::new("batch-2", [8, 32])]),
+///     Bin::new([16, 64]).with_items([Item::new("web", [12, 16]).with_priority(10)]),
+/// ];
+/// let problem = Problem::new(ResourceSchema::new(["cores", "memory_gb"]), [16, 64])
+///     .with_existing_bins(fleet)
+///     .with_items([Item::new("db", [8, 32]).with_priority(5)]);
+/// let plan = preempt(&problem, &PackOptions::new([0.5, 0.5]))?;
+/// assert_eq!(plan.result.bin_of("db"), Some(0));
+/// assert_eq!(plan.evictions.len(), 1);
+/// assert_eq!(plan.stopped().count(), 1);
+/// # Ok::<(), bin_packer::PackError>(())
+/// ```
+pub fn preempt(problem: &Problem, options: &PackOptions) -> Result<PreemptionPlan, PackError> {
+    problem.validate()?;
+    let options = &options.normalized(&problem.schema)?;
+    if !problem.constraints.is_empty() {
+        return Err(PackError::Unsupported("preemption does not support item constraints".to_string()));
+    }
+    let reference = problem.reference_capacity();
+    let constraints = ConstraintSet::default();
+    let mut bins = problem.existing_bins.clone();
+    let mut index = BinIndex::new(&bins, problem.schema.len(), options);
+
+    let mut sorted = options.ordering.sort(&problem.items, problem, &options.weights, options.tie_break_seed);
+    sorted.sort_by_key(|item| Reverse(item.priority));
+    // Items waiting to be placed, each with the index of its eviction if it
+    // was evicted. The heap holds their priorities and positions, so items
+    // are taken highest priority first and then in the order queued.
+    let mut queue: Vec<(Item, Option<usize>)> = sorted.into_iter().map(|item| (item.clone(), None)).collect();
+    let mut waiting: BinaryHeap<(i32, Reverse<usize>)> = queue.iter().enumerate().map(|(q, (item, _))| (item.priority, Reverse(q))).collect();
+    let mut evictions: Vec<Eviction> = Vec::new();
+    // Index into `evictions` of each item evicted so far.
+    let mut evicted_at: HashMap<String, usize> = HashMap::new();
+    let mut unplaced = Vec::new();
+    let mut current = None;
+
+    while let Some((_, Reverse(q))) = waiting.pop() {
+        let (item, evicted) = queue[q].clone();
+        let members = [&item];
+        let context = PlacementContext::new(&bins, &index, &item, &members, &constraints, options, current);
+        let selected = context.select(options.strategy.as_ref())?;
+        let b = match (selected, evicted) {
+            (Some(b), _) => b,
+            (None, Some(_)) => continue,
+            (None, None) => {
+                let Some((b, victims)) = victims(&bins, &item, &reference, options) else {
+                    unplaced.push(Unplaced {
+                        item,
+                        reason: UnplacedReason::NoBinAvailable,
+                    });
+                    continue;
+                };
+                for position in victims.into_iter().rev() {
+                    let victim = bins[b].remove_item(position);
+                    let e = match evicted_at.get(&victim.id) {
+                        Some(&e) => {
+                            evictions[e].rescheduled_to = None;
+                            e
+                        }
+                        None => {
+                            evicted_at.insert(victim.id.clone(), evictions.len());
+                            evictions.push(Eviction {
+                                item: victim.clone(),
+                                from: b,
+                                preempted_by: item.id.clone(),
+                                rescheduled_to: None,
+                            });
+                            evictions.len() - 1
+                        }
+                    };
+                    waiting.push((victim.priority, Reverse(queue.len())));
+                    queue.push((victim, Some(e)));
+                }
+                b
+            }
+        };
+        if let Some(e) = evicted {
+            evictions[e].rescheduled_to = Some(b);
+        }
+        bins[b].push_item(item);
+        index.update(&bins, b, options);
+        current = Some(b);
+    }
+
+    // The placed items all fit in the existing bins, so no new bins are
+    // needed, unless the weighted fit check overfilled them.
+    let lower_bound = (!matches!(options.capacity_mode, CapacityMode::Weighted)).then_some(0);
+    let existing_bins = problem.existing_bins.len();
+    let result = PackResult {
+        cost: total_cost(&bins[existing_bins..], problem),
+        bins,
+        existing_bins,
+        unplaced,
+        lower_bound,
+    };
+    Ok(PreemptionPlan { result, evictions })
+}
+
+// The bin in which evicting items of lower priority than `item` makes room
+// for it at least cost, with the positions there of the items to evict in
+// increasing order.
+fn victims(bins: &[Bin], item: &Item, reference: &[u32], options: &PackOptions) -> Option<(usize, Vec<usize>)> {
+    let mut best: Option<(usize, Vec<usize>)> = None;
+    let mut best_key = (i32::MAX, usize::MAX, f64::INFINITY);
+    for (b, bin) in bins.iter().enumerate() {
+        if !bin.has_labels(&item.required_labels) {
+            continue;
+        }
+        let items = bin.items();
+        let size = |k: usize| weighted_size(&items[k], reference, options);
+        let fits_without = |evicted: &[usize]| bin.without(evicted).fits(item, options);
+
+        // Evict the lowest priority first, and the largest among equals,
+        // until the item fits.
+        let mut candidates: Vec<usize> = (0..items.len()).filter(|&k| items[k].priority < item.priority).collect();
+        candidates.sort_by(|&x, &y| items[x].priority.cmp(&items[y].priority).then(size(y).total_cmp(&size(x))));
+        let mut evicted = Vec::new();
+        for &k in &candidates {
+            if fits_without(&evicted) {
+                break;
+            }
+            evicted.push(k);
+        }
+        if !fits_without(&evicted) {
+            continue;
+        }
+        // Then keep whichever victims the item fits without evicting,
+        // trying the highest priority first.
+        for k in evicted.clone().into_iter().rev() {
+            let kept: Vec<usize> = evicted.iter().copied().filter(|&e| e != k).collect();
+            if fits_without(&kept) {
+                evicted = kept;
+            }
+        }
+
+        let highest = evicted.iter().map(|&k| items[k].priority).max().unwrap_or(i32::MIN);
+        let key = (highest, evicted.len(), evicted.iter().map(|&k| size(k)).sum::<f64>());
+        if best.is_none() || (key.0, key.1).cmp(&(best_key.0, best_key.1)).then(key.2.total_cmp(&best_key.2)).is_lt() {
+            evicted.sort_unstable();
+            best = Some((b, evicted));
+            best_key = key;
+        }
+    }
+    best
+}
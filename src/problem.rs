@@ -0,0 +1,188 @@
+use std::collections::HashSet;
+
+use crate::bin::Bin;
+use crate::bin_type::BinType;
+use crate::constraint::Constraint;
+use crate::error::PackError;
+use crate::item::Item;
+use crate::resource::ResourceSchema;
+use crate::topology::Topology;
+
+/// A packing instance: the resource schema, the catalogue of bin types that
+/// can be opened, any bins that already exist and the items to place.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    pub schema: ResourceSchema,
+    pub bin_types: Vec<BinType>,
+    /// Bins that are already running, possibly with items in them. New items
+    /// go into these before any new bin is opened.
+    pub existing_bins: Vec<Bin>,
+    pub items: Vec<Item>,
+    /// Rules about which items may share a bin, or how they spread over the
+    /// topology.
+    pub constraints: Vec<Constraint>,
+    /// The failure domains bins belong to, for spread constraints.
+    pub topology: Topology,
+}
+
+impl Problem {
+    /// A problem with a single, unlimited bin type of unit cost, so that
+    /// minimising cost minimises the number of bins.
+    pub fn new(schema: ResourceSchema, bin_capacity: impl Into<Vec<u32>>) -> Self {
+        Problem::from_bin_types(schema, vec![BinType::new("default", bin_capacity)])
+    }
+
+    /// A problem choosing between several bin types, minimising total cost.
+    pub fn from_bin_types(schema: ResourceSchema, bin_types: Vec<BinType>) -> Self {
+        Problem {
+            schema,
+            bin_types,
+            existing_bins: Vec::new(),
+            items: Vec::new(),
+            constraints: Vec::new(),
+            topology: Topology::default(),
+        }
+    }
+
+    pub fn with_existing_bins(mut self, bins: impl IntoIterator<Item = Bin>) -> Self {
+        self.existing_bins.extend(bins);
+        self
+    }
+
+    pub fn with_items(mut self, items: impl IntoIterator<Item = Item>) -> Self {
+        self.items.extend(items);
+        self
+    }
+
+    pub fn add_item(&mut self, item: Item) {
+        self.items.push(item);
+    }
+
+    pub fn with_constraints(mut self, constraints: impl IntoIterator<Item = Constraint>) -> Self {
+        self.constraints.extend(constraints);
+        self
+    }
+
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    /// The capacities of the bin types followed by those of the existing bins.
+    pub(crate) fn capacities(&self) -> impl Iterator<Item = &[u32]> {
+        self.bin_types.iter().map(|t| t.capacity.as_slice()).chain(self.existing_bins.iter().map(|b| b.capacity.as_slice()))
+    }
+
+    /// An empty bin of each bin type whose `max_count` allows opening any,
+    /// the types whose domains spread constraints balance over.
+    pub(crate) fn openable_bins(&self) -> Vec<Bin> {
+        self.bin_types
+            .iter()
+            .enumerate()
+            .filter(|(_, bt)| bt.max_count != Some(0))
+            .map(|(t, bt)| Bin::of_type(t, bt))
+            .collect()
+    }
+
+    /// Largest capacity per dimension among the bin types and existing bins,
+    /// used to put demands in different units on a common scale.
+    pub(crate) fn reference_capacity(&self) -> Vec<u32> {
+        let mut reference = vec![1; self.schema.len()];
+        for capacity in self.capacities() {
+            for (r, &c) in reference.iter_mut().zip(capacity) {
+                *r = (*r).max(c);
+            }
+        }
+        reference
+    }
+
+    /// Whether any item to place requires bin labels.
+    pub(crate) fn has_required_labels(&self) -> bool {
+        self.items.iter().any(|item| !item.required_labels.is_empty())
+    }
+
+    /// Checks that every vector matches the schema, no bin type or existing
+    /// bin capacity is zero, costs are valid, item ids are unique across new
+    /// items and the items in existing bins, and constraints only name those
+    /// items.
+    pub(crate) fn validate(&self) -> Result<(), PackError> {
+        if !self.schema.is_empty() && self.items.is_empty() {
+            return Err(PackError::EmptyInput);
+        }
+        let mut ids = self.validate_bins()?;
+        for item in &self.items {
+            check_dimensions(&format!("item {:?} demand", item.id), &item.demand, self.schema.len())?;
+            if !ids.insert(item.id.as_str()) {
+                return Err(PackError::DuplicateItemId(item.id.clone()));
+            }
+        }
+        for (c, constraint) in self.constraints.iter().enumerate() {
+            if let Some(id) = constraint.items().iter().find(|id| !ids.contains(id.as_str())) {
+                return Err(PackError::InvalidConstraint(format!("constraint {} names unknown item {:?}", c, id)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks everything [`validate`](Problem::validate) does except the
+    /// items to place, and returns the ids of the items in existing bins.
+    pub(crate) fn validate_bins(&self) -> Result<HashSet<&str>, PackError> {
+        let dimensions = self.schema.len();
+        if dimensions == 0 {
+            return Err(PackError::EmptySchema);
+        }
+        if self.bin_types.is_empty() {
+            return Err(PackError::EmptyCatalogue);
+        }
+        for bin_type in &self.bin_types {
+            check_dimensions(&format!("bin type {:?} capacity", bin_type.name), &bin_type.capacity, dimensions)?;
+            if let Some(d) = bin_type.capacity.iter().position(|&c| c == 0) {
+                return Err(PackError::ZeroCapacity {
+                    bin_type: bin_type.name.clone(),
+                    dimension: self.schema.name(d).to_string(),
+                });
+            }
+            if !bin_type.cost.is_finite() || bin_type.cost < 0.0 {
+                return Err(PackError::InvalidCost {
+                    bin_type: bin_type.name.clone(),
+                    cost: bin_type.cost,
+                });
+            }
+        }
+        let mut ids = HashSet::with_capacity(self.items.len());
+        for (b, bin) in self.existing_bins.iter().enumerate() {
+            if let Some(bin_type) = bin.bin_type.filter(|&t| t >= self.bin_types.len()) {
+                return Err(PackError::UnknownBinType { bin: b, bin_type });
+            }
+            check_dimensions(&format!("existing bin {} capacity", b), &bin.capacity, dimensions)?;
+            if let Some(d) = bin.capacity.iter().position(|&c| c == 0) {
+                return Err(PackError::ZeroBinCapacity {
+                    bin: b,
+                    dimension: self.schema.name(d).to_string(),
+                });
+            }
+            if !bin.reserved.is_empty() {
+                check_dimensions(&format!("existing bin {} reserved", b), &bin.reserved, dimensions)?;
+            }
+            for item in bin.items() {
+                check_dimensions(&format!("item {:?} demand", item.id), &item.demand, dimensions)?;
+                if !ids.insert(item.id.as_str()) {
+                    return Err(PackError::DuplicateItemId(item.id.clone()));
+                }
+            }
+        }
+        Ok(ids)
+    }
+}
+
+pub(crate) fn check_dimensions(what: &str, values: &[u32], expected: usize) -> Result<(), PackError> {
+    if values.len() == expected {
+        Ok(())
+    } else {
+        Err(PackError::DimensionMismatch {
+            what: what.to_string(),
+            expected,
+            found: values.len(),
+        })
+    }
+}
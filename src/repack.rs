@@ -0,0 +1,221 @@
+use crate::bin::Bin;
+use crate::bin_type::BinType;
+use crate::bounds;
+use crate::constraint::ConstraintSet;
+use crate::error::PackError;
+use crate::item::Item;
+use crate::options::PackOptions;
+use crate::ordering;
+use crate::problem::Problem;
+
+/// How the effort of a migration is measured.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MoveCost {
+    /// Every moved item counts as 1.
+    #[default]
+    Count,
+    /// A moved item counts its weighted size: the weighted sum of its demands
+    /// as fractions of the largest bin.
+    WeightedSize,
+}
+
+/// Settings for [`repack`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RepackOptions {
+    /// How moves are counted, both for ranking bins to empty and against
+    /// `max_move_cost`.
+    pub move_cost: MoveCost,
+    /// Most total move cost the plan may spend; `None` for no limit.
+    pub max_move_cost: Option<f64>,
+}
+
+impl RepackOptions {
+    /// Counts moved items and allows at most `moves` of them.
+    pub fn max_moves(moves: usize) -> Self {
+        RepackOptions {
+            move_cost: MoveCost::Count,
+            max_move_cost: Some(moves as f64),
+        }
+    }
+
+    /// Weighs moved items by size and allows at most `size` in total.
+    pub fn max_moved_size(size: f64) -> Self {
+        RepackOptions {
+            move_cost: MoveCost::WeightedSize,
+            max_move_cost: Some(size),
+        }
+    }
+}
+
+/// One step of a migration plan: move `item` from bin `from` to bin `to`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Move {
+    pub item: String,
+    pub from: usize,
+    pub to: usize,
+}
+
+/// Outcome of [`repack`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepackPlan {
+    /// Moves to carry out in order. Every intermediate placement respects
+    /// every bin's capacity.
+    pub moves: Vec<Move>,
+    /// The bins once every move is done, indexed as in the input.
+    pub bins: Vec<Bin>,
+    /// Indices of the bins left empty, including any that were empty
+    /// already.
+    pub emptied: Vec<usize>,
+    /// Total move cost of the plan, as measured by
+    /// [`RepackOptions::move_cost`].
+    pub move_cost: f64,
+    /// Lower bound on the number of bins the items need, or `None` under
+    /// [`CapacityMode::Weighted`](crate::CapacityMode::Weighted).
+    pub lower_bound: Option<usize>,
+}
+
+impl RepackPlan {
+    /// Number of bins still holding items.
+    pub fn bins_used(&self) -> usize {
+        self.bins.len() - self.emptied.len()
+    }
+}
+
+/// Plans how to empty as many of `problem`'s existing bins as possible by
+/// moving their items into the other bins, within the move budget of
+/// `repack_options`.
+///
+/// Bins are emptied one at a time, cheapest to empty first, each by moving
+/// its items, largest first, into the fullest remaining bin they fit in. A
+/// bin that receives items is never emptied later, so no item moves twice
+/// and every move goes into a bin that only fills up: the moves can be
+/// carried out in plan order without ever exceeding a capacity. Bins with
+/// reserved usage cannot be emptied but can receive items. Moves keep the
+/// problem's constraints, and bins holding items that must share a bin with
+/// others or are spread over the topology are not emptied.
+///
+/// Fails if `problem` has items to place, which should be packed first, or
+/// if it is inconsistent with its schema, a constraint is invalid, or the
+/// weights or overcommit ratios in `options` are invalid.
+///
+/// ```
+/// use bin_packer::{repack, Bin, Item, PackOptions, Problem, RepackOptions, ResourceSchema};
+///
+/// let fleet = [
+///     Bin::new([16, 64]).with_items([Item::new("a", [8, 16])]),
+///     Bin::new([16, 64]).with_items([Item::new("b", [4, 32]), Item::new("c", [2, 8])]),
+///     Bin::new([16, 64]).with_items([Item::new("d", [6, 16])]),
+///     Bin::new([16, 64]).with_items([Item::new("e", [2, 8])]),
+/// ];
+/// let problem = Problem::new(ResourceSchema::new(["cores", "memory_gb"]), [16, 64]).with_existing_bins(fleet);
+/// let options = PackOptions::new([0.5, 0.5]);
+/// let plan = repack(&problem, &options, &RepackOptions::default())?;
+/// assert_eq!(plan.bins_used(), 2);
+///
+/// // Carrying out the moves in order never overfills a bin.
+/// let mut bins = problem.existing_bins.clone();
+/// for step in &plan.moves {
+///     let position = bins[step.from].items().iter().position(|item| item.id == step.item).unwrap();
+///     let item = bins[step.from].remove_item(position);
+///     assert!(bins[step.to].add_item(item, &options));
+/// }
+/// assert_eq!(bins, plan.bins);
+/// # Ok::<(), bin_packer::PackError>(())
+/// ```
+pub fn repack(problem: &Problem, options: &PackOptions, repack_options: &RepackOptions) -> Result<RepackPlan, PackError> {
+    problem.validate_bins()?;
+    if !problem.items.is_empty() {
+        return Err(PackError::Unsupported(
+            "repack only moves items between existing bins; pack new items first".to_string(),
+        ));
+    }
+    let options = &options.normalized(&problem.schema)?;
+    let constraints = ConstraintSet::new(&problem.constraints, &problem.topology)?;
+    let reference = problem.reference_capacity();
+    let move_cost = |item: &Item| match repack_options.move_cost {
+        MoveCost::Count => 1.0,
+        MoveCost::WeightedSize => ordering::weighted_sum(&ordering::normalize(item, &reference), &options.weights),
+    };
+
+    let mut bins = problem.existing_bins.clone();
+    let mut emptied = vec![false; bins.len()];
+    let mut received = vec![false; bins.len()];
+    let mut sources: Vec<(f64, usize)> = (0..bins.len())
+        .filter(|&b| bins[b].reserved.iter().all(|&r| r == 0))
+        .filter(|&b| bins[b].items().iter().all(|item| !constraints.is_pinned(&item.id)))
+        .map(|b| (bins[b].items().iter().map(move_cost).sum(), b))
+        .collect();
+    sources.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+
+    let mut moves = Vec::new();
+    let mut spent = 0.0;
+    for (cost, source) in sources {
+        if bins[source].items().is_empty() {
+            emptied[source] = true;
+            continue;
+        }
+        if received[source] || repack_options.max_move_cost.is_some_and(|max| spent + cost > max + 1e-9) {
+            continue;
+        }
+        if let Some(plan) = empty_bin(&mut bins, source, &emptied, &constraints, &reference, options) {
+            for &(i, to) in &plan {
+                moves.push(Move {
+                    item: bins[source].items()[i].id.clone(),
+                    from: source,
+                    to,
+                });
+                received[to] = true;
+            }
+            bins[source].take_items();
+            emptied[source] = true;
+            spent += cost;
+        }
+    }
+
+    let capacities = problem.existing_bins.iter().map(|bin| BinType::new("existing", bin.capacity.clone())).collect();
+    let relaxed = Problem::from_bin_types(problem.schema.clone(), capacities);
+    let items: Vec<&Item> = bins.iter().flat_map(|bin| bin.items()).collect();
+    let lower_bound = bounds::for_items(&items, &relaxed, options).map(|bounds| bounds.best());
+    Ok(RepackPlan {
+        moves,
+        bins,
+        emptied: (0..emptied.len()).filter(|&b| emptied[b]).collect(),
+        move_cost: spent,
+        lower_bound,
+    })
+}
+
+// Moves every item of bin `source`, largest first, into the fullest other
+// open bin it fits in and returns the (item index, bin) pairs, or undoes the
+// moves and returns `None` if some item fits in no other open bin. The items
+// stay in `source` either way.
+fn empty_bin(
+    bins: &mut [Bin],
+    source: usize,
+    emptied: &[bool],
+    constraints: &ConstraintSet,
+    reference: &[u32],
+    options: &PackOptions,
+) -> Option<Vec<(usize, usize)>> {
+    let size = |item: &Item| ordering::weighted_sum(&ordering::normalize(item, reference), &options.weights);
+    let mut order: Vec<usize> = (0..bins[source].items().len()).collect();
+    order.sort_by(|&a, &b| size(&bins[source].items()[b]).total_cmp(&size(&bins[source].items()[a])));
+
+    let mut plan: Vec<(usize, usize)> = Vec::with_capacity(order.len());
+    for i in order {
+        let item = bins[source].items()[i].clone();
+        let to = (0..bins.len())
+            .filter(|&b| b != source && !emptied[b] && bins[b].fits(&item, options) && constraints.admits(&bins[b], &[&item]))
+            .min_by(|&a, &b| bins[a].slack_after(&item, options).total_cmp(&bins[b].slack_after(&item, options)));
+        let Some(to) = to else {
+            for &(_, to) in plan.iter().rev() {
+                let last = bins[to].items().len() - 1;
+                bins[to].remove_item(last);
+            }
+            return None;
+        };
+        bins[to].push_item(item);
+        plan.push((i, to));
+    }
+    Some(plan)
+}
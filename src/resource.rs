@@ -0,0 +1,47 @@
+/// Names the resource dimensions that items demand and bins provide, e.g.
+/// `["cores", "memory_gb", "gpus"]`.
+///
+/// Demand and capacity vectors are indexed in schema order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ResourceSchema {
+    dimensions: Vec<String>,
+}
+
+impl ResourceSchema {
+    pub fn new<I, S>(dimensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        ResourceSchema {
+            dimensions: dimensions.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Number of dimensions.
+    pub fn len(&self) -> usize {
+        self.dimensions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dimensions.is_empty()
+    }
+
+    /// Name of the dimension at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of range.
+    pub fn name(&self, index: usize) -> &str {
+        &self.dimensions[index]
+    }
+
+    /// Index of the dimension called `name`, if there is one.
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.dimensions.iter().position(|d| d == name)
+    }
+
+    pub fn dimensions(&self) -> &[String] {
+        &self.dimensions
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::bin::{Bin, CapacityExceeded};
+use crate::item::Item;
+
+/// Outcome of a packing run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackResult {
+    /// The problem's existing bins, in input order, followed by the bins
+    /// opened by the solver. Newly opened bins are never empty.
+    pub bins: Vec<Bin>,
+    /// Number of leading entries in `bins` that were existing bins.
+    pub existing_bins: usize,
+    /// Items that could not be placed.
+    pub unplaced: Vec<Unplaced>,
+    /// Total cost of the newly opened bins.
+    pub cost: f64,
+    /// Lower bound on the number of new bins any packing of the placed items
+    /// needs; see [`lower_bounds`](crate::lower_bounds). `None` under
+    /// [`CapacityMode::Weighted`](crate::CapacityMode::Weighted), where no
+    /// bound holds.
+    pub lower_bound: Option<usize>,
+}
+
+impl PackResult {
+    /// Index into [`bins`](PackResult::bins) of the bin holding item `id`, or
+    /// `None` if it was not placed.
+    pub fn bin_of(&self, id: &str) -> Option<usize> {
+        self.bins.iter().position(|bin| bin.items().iter().any(|item| item.id == id))
+    }
+
+    /// The bins opened by the solver.
+    pub fn new_bins(&self) -> &[Bin] {
+        &self.bins[self.existing_bins..]
+    }
+
+    /// Number of bins opened by the solver.
+    pub fn bins_used(&self) -> usize {
+        self.bins.len() - self.existing_bins
+    }
+
+    /// How many more bins were opened than the lower bound, or `None` if
+    /// there is no bound.
+    pub fn gap(&self) -> Option<usize> {
+        self.lower_bound.map(|bound| self.bins_used().saturating_sub(bound))
+    }
+
+    /// [`gap`](PackResult::gap) as a fraction of the bins opened, in
+    /// `[0, 1]`; 0.0 means the packing is provably optimal.
+    pub fn relative_gap(&self) -> Option<f64> {
+        let gap = self.gap()?;
+        Some(match self.bins_used() {
+            0 => 0.0,
+            used => gap as f64 / used as f64,
+        })
+    }
+
+    /// Fraction of the total capacity of [`bins`](PackResult::bins) in
+    /// dimension `d` that is in use, reserved usage included. Above 1.0 if
+    /// bins are overcommitted.
+    pub fn utilisation(&self, d: usize) -> f64 {
+        let capacity: u64 = self.bins.iter().map(|bin| bin.capacity[d] as u64).sum();
+        let used: u64 = self.bins.iter().map(|bin| bin.used(d)).sum();
+        match capacity {
+            0 => 0.0,
+            capacity => used as f64 / capacity as f64,
+        }
+    }
+
+    /// Bin index of every placed item, keyed by item id.
+    pub fn assignments(&self) -> BTreeMap<&str, usize> {
+        self.bins
+            .iter()
+            .enumerate()
+            .flat_map(|(b, bin)| bin.items().iter().map(move |item| (item.id.as_str(), b)))
+            .collect()
+    }
+}
+
+/// An item that could not be placed, with the reason why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unplaced {
+    pub item: Item,
+    pub reason: UnplacedReason,
+}
+
+/// Why an item was left out of the packing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnplacedReason {
+    /// The item is larger than an empty bin of every type. Lists the
+    /// dimensions exceeded for the bin type that came closest.
+    Oversized {
+        bin_type: String,
+        exceeded: Vec<CapacityExceeded>,
+    },
+    /// The item fits some bin type, but no open bin has room and every type
+    /// it fits has reached its `max_count`.
+    NoBinAvailable,
+    /// No bin type carries every label the item requires, and no open bin
+    /// that does has room.
+    NoMatchingBinType,
+    /// Every bin and bin type the item fits in is outside the domains of an
+    /// enforced spread constraint the item is in, or would take it over its
+    /// maximum skew.
+    SpreadLimit,
+}
+
+impl fmt::Display for Unplaced {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "item {} {:?} ", self.item.id, self.item.demand)?;
+        match &self.reason {
+            UnplacedReason::Oversized { bin_type, exceeded } => {
+                write!(f, "does not fit in any bin, closest is {}:", bin_type)?;
+                for (i, exceeded) in exceeded.iter().enumerate() {
+                    let separator = if i == 0 { " " } else { ", " };
+                    write!(f, "{}{}", separator, exceeded)?;
+                }
+                Ok(())
+            }
+            UnplacedReason::NoBinAvailable => f.write_str("fits no open bin and no more bins may be opened"),
+            UnplacedReason::NoMatchingBinType => {
+                let labels: Vec<String> = self.item.required_labels.iter().map(|(key, value)| format!("{}={}", key, value)).collect();
+                write!(f, "requires labels {} that no bin type carries", labels.join(", "))
+            }
+            UnplacedReason::SpreadLimit => f.write_str("fits no bin that keeps its spread constraints"),
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use std::fmt;
+
+use crate::bin::Bin;
+use crate::constraint::ConstraintSet;
+use crate::error::PackError;
+use crate::index::{Allowed, BinIndex};
+use crate::item::Item;
+use crate::options::PackOptions;
+
+/// What a [`PlacementStrategy`] sees when placing one item.
+///
+/// Items that an affinity [`Constraint`](crate::Constraint) puts in the same
+/// bin are placed together, as one item with their total demand and the id
+/// of the first of them. All built-in strategies favour bins that keep
+/// preferred spread constraints.
+#[derive(Debug)]
+pub struct PlacementContext<'a> {
+    bins: &'a [Bin],
+    index: &'a BinIndex,
+    item: &'a Item,
+    // The items actually placed: `item` itself, or the items it stands for.
+    members: &'a [&'a Item],
+    constraints: &'a ConstraintSet,
+    options: &'a PackOptions,
+    current: Option<usize>,
+}
+
+impl<'a> PlacementContext<'a> {
+    pub(crate) fn new(
+        bins: &'a [Bin],
+        index: &'a BinIndex,
+        item: &'a Item,
+        members: &'a [&'a Item],
+        constraints: &'a ConstraintSet,
+        options: &'a PackOptions,
+        current: Option<usize>,
+    ) -> Self {
+        PlacementContext {
+            bins,
+            index,
+            item,
+            members,
+            constraints,
+            options,
+            current,
+        }
+    }
+
+    /// Existing bins followed by the bins opened so far.
+    pub fn bins(&self) -> &[Bin] {
+        self.bins
+    }
+
+    /// The item being placed.
+    pub fn item(&self) -> &Item {
+        self.item
+    }
+
+    /// The bin that received the previous item, if any.
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+
+    /// Whether the item fits in `bin` and may go there under the problem's
+    /// constraints.
+    pub fn fits(&self, bin: usize) -> bool {
+        self.bins[bin].fits(self.item, self.options) && self.allowed(bin)
+    }
+
+    /// Indices of the bins the item fits in, in order.
+    pub fn candidates(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.bins.len()).filter(|&b| self.fits(b))
+    }
+
+    /// Weighted remaining capacity of `bin` once the item is added: the sum
+    /// over dimensions of weight times the fraction of capacity left. 0.0 is
+    /// a perfectly full bin, 1.0 an empty one.
+    pub fn slack(&self, bin: usize) -> f64 {
+        self.bins[bin].slack_after(self.item, self.options)
+    }
+
+    /// How many of the item's preferred labels `bin` carries.
+    pub fn preference(&self, bin: usize) -> usize {
+        self.bins[bin].matching_labels(&self.item.preferred_labels)
+    }
+
+    /// How many spread constraints that are only preferred putting the item
+    /// in `bin` would break.
+    pub fn spread_violations(&self, bin: usize) -> usize {
+        self.constraints.spread_violations(&self.bins[bin], self.members)
+    }
+
+    // Asks `strategy` for a bin and checks that the item fits in it and the
+    // constraints allow it there, which a strategy is trusted for no more
+    // than it has to be.
+    pub(crate) fn select(&self, strategy: &dyn PlacementStrategy) -> Result<Option<usize>, PackError> {
+        match strategy.select(self) {
+            Some(bin) if bin >= self.bins.len() || !self.fits(bin) => Err(PackError::InvalidStrategy {
+                item: self.item.id.clone(),
+                bin,
+            }),
+            selected => Ok(selected),
+        }
+    }
+
+    // Whether the constraints let the item into `bin`.
+    fn allowed(&self, bin: usize) -> bool {
+        self.constraints.admits(&self.bins[bin], self.members)
+    }
+
+    // Indexed lookups for the built-in strategies, which give the same bins
+    // as scanning `candidates`, as tests/strategy.rs checks. Bins breaking
+    // fewer preferred spread constraints are searched first and, for the
+    // best-fit style lookups, among those the bins carrying the most
+    // preferred labels.
+
+    pub(crate) fn first_fit(&self, from: usize) -> Option<usize> {
+        self.ranked(false, |allowed| self.index.first_fit(self.bins, self.item, self.options, allowed, from))
+    }
+
+    pub(crate) fn best_fit(&self) -> Option<usize> {
+        self.ranked(true, |allowed| self.index.best_fit(self.bins, self.item, self.options, allowed))
+    }
+
+    pub(crate) fn worst_fit(&self, exclude: Option<usize>) -> Option<usize> {
+        self.ranked(true, |allowed| self.index.worst_fit(self.bins, self.item, self.options, allowed, exclude))
+    }
+
+    // Runs `lookup` over each rank of bins in turn, best first, until it
+    // finds a bin.
+    fn ranked(&self, by_preference: bool, lookup: impl Fn(Allowed<'_>) -> Option<usize>) -> Option<usize> {
+        let spreads = self.constraints.preferred_spreads(self.members);
+        let preferred = if by_preference { self.item.preferred_labels.len() } else { 0 };
+        if spreads == 0 && preferred == 0 {
+            return lookup(&|b| self.allowed(b));
+        }
+        (0..=spreads).find_map(|violations| {
+            (0..=preferred).rev().find_map(|matching| {
+                lookup(&|b| {
+                    self.allowed(b)
+                        && (spreads == 0 || self.spread_violations(b) == violations)
+                        && (preferred == 0 || self.preference(b) == matching)
+                })
+            })
+        })
+    }
+}
+
+/// Decides which bin an item goes into.
+///
+/// Strategies are consulted for every item in packing order and must only
+/// return bins the item [`fits`](PlacementContext::fits) in; the packing
+/// fails with [`PackError::InvalidStrategy`] otherwise.
+pub trait PlacementStrategy: fmt::Debug + Send + Sync {
+    /// Index into [`PlacementContext::bins`] of the bin to use, or `None` to
+    /// open a new bin.
+    fn select(&self, ctx: &PlacementContext<'_>) -> Option<usize>;
+}
+
+/// Only tries the bin that received the previous item and the bins after
+/// it, so earlier bins are never revisited. With no existing bins this is
+/// classic Next-Fit: a single open bin that is closed once an item does not
+/// fit.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NextFit;
+
+impl PlacementStrategy for NextFit {
+    fn select(&self, ctx: &PlacementContext<'_>) -> Option<usize> {
+        ctx.first_fit(ctx.current().unwrap_or(0))
+    }
+}
+
+/// Uses the first bin the item fits in.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FirstFit;
+
+impl PlacementStrategy for FirstFit {
+    fn select(&self, ctx: &PlacementContext<'_>) -> Option<usize> {
+        ctx.first_fit(0)
+    }
+}
+
+/// Uses the bin with the least slack left after placing the item,
+/// consolidating load, among the bins carrying the most of the item's
+/// preferred labels.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BestFit;
+
+impl PlacementStrategy for BestFit {
+    fn select(&self, ctx: &PlacementContext<'_>) -> Option<usize> {
+        ctx.best_fit()
+    }
+}
+
+/// Uses the bin with the most slack left after placing the item, keeping
+/// headroom evenly spread, among the bins carrying the most of the item's
+/// preferred labels.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WorstFit;
+
+impl PlacementStrategy for WorstFit {
+    fn select(&self, ctx: &PlacementContext<'_>) -> Option<usize> {
+        ctx.worst_fit(None)
+    }
+}
+
+/// Uses the bin with the second most slack left after placing the item, or
+/// the only candidate if there is just one. Bins carrying more of the item's
+/// preferred labels rank first, as for [`WorstFit`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AlmostWorstFit;
+
+impl PlacementStrategy for AlmostWorstFit {
+    fn select(&self, ctx: &PlacementContext<'_>) -> Option<usize> {
+        let worst = ctx.worst_fit(None)?;
+        ctx.worst_fit(Some(worst)).or(Some(worst))
+    }
+}
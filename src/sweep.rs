@@ -0,0 +1,122 @@
+use crate::bin::Bin;
+use crate::error::PackError;
+use crate::ffd::pack;
+use crate::multistart::{in_parallel, MultiStartOptions};
+use crate::options::PackOptions;
+use crate::problem::Problem;
+use crate::result::PackResult;
+
+/// How the packing of one configuration of a [`sweep`] turned out.
+#[derive(Debug, Clone)]
+pub struct SweepPoint {
+    /// The options the configuration packed with.
+    pub options: PackOptions,
+    pub bins_used: usize,
+    pub cost: f64,
+    /// Number of items left unplaced.
+    pub unplaced: usize,
+    /// Per dimension, the fraction of the bins' capacity in use; see
+    /// [`PackResult::utilisation`].
+    pub utilisation: Vec<f64>,
+    /// Per dimension, the free capacity of bins that cannot take even the
+    /// smallest demand of any item in some other dimension, as a fraction
+    /// of the bins' capacity. This capacity is left over but unusable.
+    pub stranded: Vec<f64>,
+    /// Whether no other point is at least as good on unplaced items, cost,
+    /// bins used and stranded capacity in every dimension, and better on one.
+    pub pareto_optimal: bool,
+}
+
+impl SweepPoint {
+    // What the Pareto front minimises.
+    fn objectives(&self) -> impl Iterator<Item = f64> + '_ {
+        [self.unplaced as f64, self.cost, self.bins_used as f64].into_iter().chain(self.stranded.iter().copied())
+    }
+
+    fn dominates(&self, other: &SweepPoint) -> bool {
+        let pairs = || self.objectives().zip(other.objectives());
+        pairs().all(|(a, b)| a <= b) && pairs().any(|(a, b)| a < b)
+    }
+}
+
+/// Outcome of [`sweep`].
+#[derive(Debug, Clone)]
+pub struct SweepReport {
+    /// One point per configuration, in the order they were generated.
+    pub points: Vec<SweepPoint>,
+}
+
+impl SweepReport {
+    /// The Pareto-optimal points: the trade-offs worth choosing between.
+    pub fn pareto_front(&self) -> impl Iterator<Item = &SweepPoint> {
+        self.points.iter().filter(|point| point.pareto_optimal)
+    }
+}
+
+/// Packs `problem` with every configuration of `configurations`, typically
+/// a grid of weight vectors, in parallel, and reports the bins used,
+/// utilisation and stranded capacity of each along with which
+/// configurations are Pareto-optimal. The objective of `configurations` is
+/// not used.
+///
+/// Settings not varied by `configurations` are taken from `options`. Fails
+/// with the error of the first configuration that fails.
+///
+/// ```
+/// use bin_packer::{sweep, Item, MultiStartOptions, PackOptions, Problem, ResourceSchema};
+///
+/// let items = (0..30).map(|i| Item::new(format!("job-{}", i), [1 + i % 6, 20 + 35 * (i % 4)]));
+/// let problem = Problem::new(ResourceSchema::new(["cores", "disk_gb"]), [10, 200]).with_items(items);
+/// let grid = MultiStartOptions::default().with_weight_grid(2, 10);
+/// let report = sweep(&problem, &PackOptions::new([0.5, 0.5]), &grid)?;
+/// assert_eq!(report.points.len(), 11);
+/// for point in report.pareto_front() {
+///     println!("{:?}: {} bins, stranded {:?}", point.options.weights, point.bins_used, point.stranded);
+/// }
+/// # Ok::<(), bin_packer::PackError>(())
+/// ```
+pub fn sweep(problem: &Problem, options: &PackOptions, configurations: &MultiStartOptions) -> Result<SweepReport, PackError> {
+    problem.validate()?;
+    let runs = configurations.configurations(options);
+    let dimensions = problem.schema.len();
+    let smallest: Vec<u32> = (0..dimensions).map(|d| problem.items.iter().map(|item| item.demand[d]).min().unwrap_or(0)).collect();
+
+    let outcomes = in_parallel(runs.len(), configurations.threads, Vec::new, |points, run| {
+        let configuration = &runs[run];
+        let point = pack(problem, configuration).map(|result| SweepPoint {
+            options: configuration.clone(),
+            bins_used: result.bins_used(),
+            cost: result.cost,
+            unplaced: result.unplaced.len(),
+            utilisation: (0..dimensions).map(|d| result.utilisation(d)).collect(),
+            stranded: stranded(&result, &smallest, configuration),
+            pareto_optimal: false,
+        });
+        points.push((run, point));
+    });
+    let mut points: Vec<(usize, Result<SweepPoint, PackError>)> = outcomes.into_iter().flatten().collect();
+    points.sort_by_key(|(run, _)| *run);
+    let mut points = points.into_iter().map(|(_, point)| point).collect::<Result<Vec<_>, _>>()?;
+
+    for p in 0..points.len() {
+        points[p].pareto_optimal = !points.iter().any(|other| other.dominates(&points[p]));
+    }
+    Ok(SweepReport { points })
+}
+
+// Per dimension, the free capacity of bins that some other dimension has
+// blocked, as a fraction of the bins' total capacity.
+fn stranded(result: &PackResult, smallest: &[u32], options: &PackOptions) -> Vec<f64> {
+    let dimensions = smallest.len();
+    let blocked = |bin: &Bin, d: usize| (0..dimensions).any(|e| e != d && smallest[e] > 0 && bin.free(e, options) < smallest[e] as u64);
+    (0..dimensions)
+        .map(|d| {
+            let capacity: u64 = result.bins.iter().map(|bin| bin.capacity[d] as u64).sum();
+            let stranded: u64 = result.bins.iter().filter(|bin| blocked(bin, d)).map(|bin| bin.free(d, options)).sum();
+            match capacity {
+                0 => 0.0,
+                capacity => stranded as f64 / capacity as f64,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,106 @@
+use std::collections::BTreeMap;
+
+use crate::bin::Bin;
+use crate::constraint::Constraint;
+use crate::problem::Problem;
+use crate::result::PackResult;
+
+/// The failure domains bins belong to, as a hierarchy of label keys from the
+/// finest level to the coarsest, e.g. rack, zone and region.
+///
+/// A bin's domain at a level is read from its [`labels`](Bin::labels): the
+/// value of that level's key, qualified by the values of the coarser levels,
+/// so that zone `a` of region `eu` is `eu/a` and distinct from zone `a` of
+/// region `us`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Topology {
+    pub levels: Vec<String>,
+}
+
+impl Topology {
+    pub fn new<S: Into<String>>(levels: impl IntoIterator<Item = S>) -> Self {
+        Topology {
+            levels: levels.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Position of the level with label key `key`, finest first.
+    pub fn level(&self, key: &str) -> Option<usize> {
+        self.levels.iter().position(|level| level == key)
+    }
+
+    /// The domain of `bin` at `level`, or `None` if the bin lacks that
+    /// level's label. Coarser levels the bin has no label for are left out.
+    pub fn domain(&self, bin: &Bin, level: usize) -> Option<String> {
+        bin.labels.get(&self.levels[level])?;
+        let values: Vec<&str> = self.levels[level..].iter().rev().filter_map(|key| bin.labels.get(key).map(String::as_str)).collect();
+        Some(values.join("/"))
+    }
+
+    // Every domain at `level` of `bins`, with no items counted.
+    pub(crate) fn domains<'a>(&self, level: usize, bins: impl IntoIterator<Item = &'a Bin>) -> BTreeMap<String, usize> {
+        bins.into_iter().filter_map(|bin| self.domain(bin, level)).map(|domain| (domain, 0)).collect()
+    }
+}
+
+/// How the items of a spread [`Constraint`] are spread over their domains in
+/// a packing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpreadReport {
+    /// Index into [`Problem::constraints`] of the constraint.
+    pub constraint: usize,
+    /// The topology level spread over.
+    pub level: String,
+    /// Number of the constraint's items in each domain of the level,
+    /// including domains of bin types that hold none of them.
+    pub counts: BTreeMap<String, usize>,
+    /// Most items in a domain minus fewest.
+    pub skew: usize,
+    pub max_skew: usize,
+    /// Items of the constraint placed in bins outside every domain of the
+    /// level.
+    pub outside: usize,
+}
+
+impl SpreadReport {
+    /// Whether the skew is within the constraint's maximum and no item is
+    /// outside the level's domains.
+    pub fn satisfied(&self) -> bool {
+        self.skew <= self.max_skew && self.outside == 0
+    }
+}
+
+impl PackResult {
+    /// How the items of each of `problem`'s spread constraints are spread
+    /// over their domains, whether the constraint is enforced or only
+    /// preferred. `problem` is the one packed, and supplies the topology and
+    /// the bin types whose domains count even when no bin of them is open,
+    /// unless their `max_count` is zero.
+    pub fn spread(&self, problem: &Problem) -> Vec<SpreadReport> {
+        let empty_bins = problem.openable_bins();
+        let mut reports = Vec::new();
+        for (c, constraint) in problem.constraints.iter().enumerate() {
+            let Constraint::Spread { items, level, max_skew, .. } = constraint else { continue };
+            let Some(l) = problem.topology.level(level) else { continue };
+            let mut counts = problem.topology.domains(l, self.bins.iter().chain(&empty_bins));
+            let mut outside = 0;
+            for bin in &self.bins {
+                let members = bin.items().iter().filter(|item| items.contains(&item.id)).count();
+                match problem.topology.domain(bin, l) {
+                    Some(domain) => *counts.entry(domain).or_default() += members,
+                    None => outside += members,
+                }
+            }
+            let skew = counts.values().max().unwrap_or(&0) - counts.values().min().unwrap_or(&0);
+            reports.push(SpreadReport {
+                constraint: c,
+                level: level.clone(),
+                counts,
+                skew,
+                max_skew: *max_skew,
+                outside,
+            });
+        }
+        reports
+    }
+}
@@ -0,0 +1,103 @@
+use bin_packer::{pack, Bin, BinType, Constraint, Item, PackError, PackOptions, Packer, Problem, ResourceSchema, Topology};
+
+fn schema() -> ResourceSchema {
+    ResourceSchema::new(["cores", "memory_gb"])
+}
+
+fn infeasible(result: Result<impl std::fmt::Debug, PackError>) -> String {
+    match result {
+        Err(PackError::Infeasible(reason)) => reason,
+        other => panic!("expected an infeasible problem, got {:?}", other),
+    }
+}
+
+#[test]
+fn anti_affinity_needing_more_bins_than_may_be_opened_is_infeasible() {
+    let items = (0..3).map(|i| Item::new(format!("replica-{}", i), [1, 1]));
+    let bin_types = vec![BinType::new("small", [8, 8]).with_max_count(2)];
+    let problem = Problem::from_bin_types(schema(), bin_types)
+        .with_items(items)
+        .with_constraints([Constraint::anti_affinity(["replica-0", "replica-1", "replica-2"])]);
+    let reason = infeasible(pack(&problem, &PackOptions::new([0.5, 0.5])));
+    assert!(reason.contains("need at least 3 bins but at most 2"), "{}", reason);
+
+    // An existing bin makes room for the third replica.
+    let problem = problem.with_existing_bins([Bin::new([8, 8])]);
+    assert_eq!(pack(&problem, &PackOptions::new([0.5, 0.5])).unwrap().bins.len(), 3);
+}
+
+#[test]
+fn an_affinity_group_already_split_over_existing_bins_is_infeasible() {
+    let fleet = [Bin::new([8, 8]).with_items([Item::new("web", [1, 1])]), Bin::new([8, 8]).with_items([Item::new("cache", [1, 1])])];
+    let problem = Problem::new(schema(), [8, 8])
+        .with_existing_bins(fleet)
+        .with_items([Item::new("other", [1, 1])])
+        .with_constraints([Constraint::affinity(["web", "cache"])]);
+    let reason = infeasible(pack(&problem, &PackOptions::new([0.5, 0.5])));
+    assert!(reason.contains("already in existing bins {0, 1}"), "{}", reason);
+
+    // Also when a member is still to be placed.
+    let problem = Problem {
+        constraints: vec![Constraint::affinity(["web", "cache", "other"])],
+        ..problem
+    };
+    infeasible(pack(&problem, &PackOptions::new([0.5, 0.5])));
+}
+
+#[test]
+fn an_affinity_group_that_cannot_join_its_existing_bin_is_infeasible() {
+    let fleet = [Bin::new([8, 8]).with_items([Item::new("web", [6, 6])])];
+    let problem = Problem::new(schema(), [8, 8])
+        .with_existing_bins(fleet.clone())
+        .with_items([Item::new("cache", [4, 4])])
+        .with_constraints([Constraint::affinity(["web", "cache"])]);
+    let reason = infeasible(pack(&problem, &PackOptions::new([0.5, 0.5])));
+    assert!(reason.contains("must join existing bin 0"), "{}", reason);
+
+    let mut packer = Packer::new(&Problem { items: Vec::new(), ..problem }, &PackOptions::new([0.5, 0.5])).unwrap();
+    infeasible(packer.insert(Item::new("cache", [4, 4])));
+    assert_eq!(packer.len(), 1);
+}
+
+#[test]
+fn an_affinity_group_fitting_no_bin_together_is_infeasible() {
+    let problem = Problem::new(schema(), [8, 8])
+        .with_items([Item::new("web", [5, 1]), Item::new("cache", [5, 1])])
+        .with_constraints([Constraint::affinity(["web", "cache"])]);
+    let reason = infeasible(pack(&problem, &PackOptions::new([0.5, 0.5])));
+    assert!(reason.contains("fit in no bin type together"), "{}", reason);
+}
+
+#[test]
+fn an_affinity_group_requiring_different_labels_is_infeasible() {
+    let problem = Problem::new(schema(), [8, 8])
+        .with_items([Item::new("web", [1, 1]).with_required_label("zone", "a"), Item::new("cache", [1, 1]).with_required_label("zone", "b")])
+        .with_constraints([Constraint::affinity(["web", "cache"])]);
+    let reason = infeasible(pack(&problem, &PackOptions::new([0.5, 0.5])));
+    assert!(reason.contains("require different values of label zone"), "{}", reason);
+}
+
+#[test]
+fn spread_ignores_the_domains_of_bin_types_that_cannot_be_opened() {
+    let replicas = ["replica-0", "replica-1", "replica-2", "replica-3"];
+    let bin_types = vec![BinType::new("small", [8, 8]).with_label("zone", "a"), BinType::new("retired", [8, 8]).with_label("zone", "b").with_max_count(0)];
+    let problem = Problem::from_bin_types(schema(), bin_types)
+        .with_topology(Topology::new(["zone"]))
+        .with_items(replicas.map(|id| Item::new(id, [1, 1])))
+        .with_constraints([Constraint::spread(replicas, "zone", 1)]);
+    let result = pack(&problem, &PackOptions::new([0.5, 0.5])).unwrap();
+    assert!(result.unplaced.is_empty(), "{:?}", result.unplaced);
+    let report = &result.spread(&problem)[0];
+    assert_eq!(report.counts.iter().collect::<Vec<_>>(), [(&"a".to_string(), &4)]);
+    assert!(report.satisfied());
+
+    let mut packer = Packer::new(&Problem { items: Vec::new(), ..problem.clone() }, &PackOptions::new([0.5, 0.5])).unwrap();
+    for item in &problem.items {
+        packer.insert(item.clone()).unwrap();
+    }
+
+    // An existing bin in zone b counts again.
+    let problem = problem.with_existing_bins([Bin::new([8, 8]).with_label("zone", "b")]);
+    let result = pack(&problem, &PackOptions::new([0.5, 0.5])).unwrap();
+    assert_eq!(result.spread(&problem)[0].counts.values().collect::<Vec<_>>(), [&2, &2]);
+}
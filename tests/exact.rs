@@ -0,0 +1,78 @@
+use bin_packer::{pack, solve_exact, Budget, Item, PackOptions, Problem, ResourceSchema};
+
+fn problem(demands: &[u32]) -> Problem {
+    let items = demands.iter().enumerate().map(|(i, &q)| Item::new(format!("job-{}", i), [q]));
+    Problem::new(ResourceSchema::new(["cores"]), [100]).with_items(items)
+}
+
+// Every item is over a fifth of a bin and most are over a third, so open bins
+// soon have room for one more item at most. L2 of the residual instance sees
+// this where L1 only sees the free space: bounding nodes by L1 alone explores
+// 211 nodes to prove 5 bins optimal.
+#[test]
+fn residual_l2_bound_prunes_more_than_l1() {
+    let problem = problem(&[61, 20, 24, 45, 44, 44, 37, 20, 37, 37, 29]);
+    let options = PackOptions::new([1.0]);
+    let heuristic = pack(&problem, &options).unwrap();
+    assert_eq!((heuristic.bins_used(), heuristic.lower_bound), (5, Some(4)));
+
+    let solution = solve_exact(&problem, &options, &Budget::iterations(10_000)).unwrap();
+    assert_eq!(solution.result.bins_used(), 5);
+    assert!(solution.optimal);
+    assert!(solution.nodes < 211 / 10, "{} nodes", solution.nodes);
+    assert!(solve_exact(&problem, &options, &Budget::iterations(solution.nodes)).unwrap().optimal);
+}
+
+// SplitMix64, enough for generating instances.
+struct Rng(u64);
+
+impl Rng {
+    fn below(&mut self, n: u64) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        (z ^ (z >> 31)) % n
+    }
+}
+
+// The fewest bins of capacity `capacity` the items fit in, trying every way
+// of assigning items k.. to the bins with `loads` or a new one.
+fn fewest_bins(demands: &[Vec<u64>], capacity: &[u64], k: usize, loads: &mut Vec<Vec<u64>>) -> usize {
+    if k == demands.len() {
+        return loads.len();
+    }
+    let mut best = usize::MAX;
+    for b in 0..=loads.len() {
+        if b == loads.len() {
+            loads.push(vec![0; capacity.len()]);
+        }
+        if (0..capacity.len()).all(|d| loads[b][d] + demands[k][d] <= capacity[d]) {
+            (0..capacity.len()).for_each(|d| loads[b][d] += demands[k][d]);
+            best = best.min(fewest_bins(demands, capacity, k + 1, loads));
+            (0..capacity.len()).for_each(|d| loads[b][d] -= demands[k][d]);
+        }
+        if loads[b].iter().all(|&load| load == 0) {
+            loads.pop();
+        }
+    }
+    best
+}
+
+// The bounds prune only nodes that cannot lead to a better packing, so the
+// proven optimum is the one an exhaustive search finds.
+#[test]
+fn proven_optima_match_an_exhaustive_search() {
+    let mut rng = Rng(3);
+    for _ in 0..200 {
+        let dimensions = 1 + rng.below(2) as usize;
+        let capacity: Vec<u64> = (0..dimensions).map(|_| 20 + rng.below(30)).collect();
+        let demands: Vec<Vec<u64>> = (0..2 + rng.below(7)).map(|_| capacity.iter().map(|&c| 1 + rng.below(c * 2 / 3)).collect()).collect();
+        let items = demands.iter().enumerate().map(|(i, demand)| Item::new(format!("job-{}", i), demand.iter().map(|&q| q as u32).collect::<Vec<u32>>()));
+        let schema = ResourceSchema::new((0..dimensions).map(|d| format!("r{}", d)));
+        let problem = Problem::new(schema, capacity.iter().map(|&c| c as u32).collect::<Vec<u32>>()).with_items(items);
+        let solution = solve_exact(&problem, &PackOptions::new(vec![1.0; dimensions]), &Budget::iterations(1_000_000)).unwrap();
+        assert!(solution.optimal);
+        assert_eq!(solution.result.bins_used(), fewest_bins(&demands, &capacity, 0, &mut Vec::new()), "{:?} in {:?}", demands, capacity);
+    }
+}
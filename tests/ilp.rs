@@ -0,0 +1,109 @@
+use bin_packer::{pack, Bin, BinType, IlpModel, Item, PackError, PackOptions, PackResult, Problem, ResourceSchema};
+
+fn problem() -> Problem {
+    let items = (0..12).map(|i| Item::new(format!("job-{}", i), [1 + i % 5, 10 + 7 * (i % 4)]));
+    Problem::new(ResourceSchema::new(["cores", "memory_gb"]), [8, 64])
+        .with_existing_bins([Bin::with_reserved([8, 64], [4, 16])])
+        .with_items(items)
+}
+
+// A solution assigning each item to the bin `pack` put it in, one line per
+// item written by `line`. With a single bin type, the new bins of the packing
+// are the model's first candidate new bins, in order.
+fn solution(problem: &Problem, result: &PackResult, line: impl Fn(usize, String) -> String) -> String {
+    problem
+        .items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| line(i, format!("x_{}_{}", i, result.bin_of(&item.id).unwrap())))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn assert_same_packing(problem: &Problem, parsed: &PackResult, result: &PackResult) {
+    assert_eq!(parsed.existing_bins, result.existing_bins);
+    assert_eq!(parsed.bins_used(), result.bins_used());
+    assert_eq!(parsed.cost, result.cost);
+    for item in &problem.items {
+        assert_eq!(parsed.bin_of(&item.id), result.bin_of(&item.id), "{}", item.id);
+    }
+}
+
+#[test]
+fn parse_solution_reads_back_a_packing() {
+    let problem = problem();
+    let options = PackOptions::new([0.5, 0.5]);
+    let result = pack(&problem, &options).unwrap();
+    let model = IlpModel::new(&problem, &options).unwrap();
+
+    let gurobi = format!("# Objective value = {}\n{}\ny_1 1\n", result.cost, solution(&problem, &result, |_, x| format!("{} 1", x)));
+    assert_same_packing(&problem, &model.parse_solution(&gurobi).unwrap(), &result);
+
+    let cbc = format!("Optimal - objective value {}\n{}\n", result.cost, solution(&problem, &result, |n, x| format!("{:>6} {} 1 0", n, x)));
+    assert_same_packing(&problem, &model.parse_solution(&cbc).unwrap(), &result);
+}
+
+#[test]
+fn parse_solution_rejects_invalid_assignments() {
+    let problem = problem();
+    let options = PackOptions::new([0.5, 0.5]);
+    let result = pack(&problem, &options).unwrap();
+    let model = IlpModel::new(&problem, &options).unwrap();
+    let valid = solution(&problem, &result, |_, x| format!("{} 1", x));
+    let reason = |solution: &str| match model.parse_solution(solution) {
+        Err(PackError::InvalidSolution(reason)) => reason,
+        other => panic!("expected an invalid solution, got {:?}", other),
+    };
+
+    let other = (result.bin_of("job-0").unwrap() + 1) % result.bins.len();
+    assert_eq!(reason(&format!("{}\nx_0_{} 1", valid, other)), "item job-0 is assigned to more than one bin");
+    assert_eq!(reason(&valid.lines().skip(1).collect::<Vec<_>>().join("\n")), "item job-0 is not assigned to a bin");
+    let crowded: String = (0..problem.items.len()).map(|i| format!("x_{}_0 1\n", i)).collect();
+    assert!(reason(&crowded).contains("exceeds the capacity of bin 0"));
+}
+
+#[test]
+fn write_lp_writes_the_assignment_model() {
+    let problem = Problem::new(ResourceSchema::new(["cores", "memory_gb"]), [4, 8]).with_items([Item::new("a", [2, 4]), Item::new("b", [3, 0])]);
+    let model = IlpModel::new(&problem, &PackOptions::new([0.5, 0.5])).unwrap();
+    let mut lp = Vec::new();
+    model.write_lp(&mut lp).unwrap();
+    let expected = "\
+\\ Bin packing assignment model
+\\ item 0 = a
+\\ item 1 = b
+Minimize
+ obj: 1 y_0 + 1 y_1
+Subject To
+ assign_0: 1 x_0_0 + 1 x_0_1 = 1
+ assign_1: 1 x_1_0 + 1 x_1_1 = 1
+ cap_0_0: 2 x_0_0 + 3 x_1_0 - 4 y_0 <= 0
+ cap_0_1: 4 x_0_0 - 8 y_0 <= 0
+ cap_1_0: 2 x_0_1 + 3 x_1_1 - 4 y_1 <= 0
+ cap_1_1: 4 x_0_1 - 8 y_1 <= 0
+ order_1: y_0 - y_1 >= 0
+Binary
+ x_0_0
+ x_1_0
+ x_0_1
+ x_1_1
+ y_0
+ y_1
+End
+";
+    assert_eq!(String::from_utf8(lp).unwrap(), expected);
+    assert_eq!(model.variables(), 6);
+}
+
+#[test]
+fn candidate_new_bins_are_capped_by_items_and_max_count() {
+    // Both items fit in one bin, but the model offers a bin for each.
+    let items = [Item::new("a", [1, 1]), Item::new("b", [1, 1])];
+    let problem = Problem::new(ResourceSchema::new(["cores", "memory_gb"]), [4, 8]).with_items(items.clone());
+    assert_eq!(pack(&problem, &PackOptions::new([0.5, 0.5])).unwrap().bins_used(), 1);
+    assert_eq!(IlpModel::new(&problem, &PackOptions::new([0.5, 0.5])).unwrap().variables(), 2 * 2 + 2);
+
+    let bin_types = vec![BinType::new("small", [4, 8]).with_max_count(1), BinType::new("large", [8, 16]).with_cost(3.0)];
+    let problem = Problem::from_bin_types(ResourceSchema::new(["cores", "memory_gb"]), bin_types).with_items(items);
+    assert_eq!(IlpModel::new(&problem, &PackOptions::new([0.5, 0.5])).unwrap().variables(), 2 * 3 + 3);
+}
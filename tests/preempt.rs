@@ -0,0 +1,96 @@
+use bin_packer::{preempt, Bin, Item, PackOptions, Problem, ResourceSchema, UnplacedReason};
+
+fn problem(fleet: impl IntoIterator<Item = Bin>, items: impl IntoIterator<Item = Item>) -> Problem {
+    Problem::new(ResourceSchema::new(["cores", "memory_gb"]), [10, 10]).with_existing_bins(fleet).with_items(items)
+}
+
+#[test]
+fn items_are_placed_highest_priority_first() {
+    let items = [Item::new("low", [6, 6]).with_priority(1), Item::new("high", [6, 6]).with_priority(5), Item::new("same", [6, 6]).with_priority(5)];
+    let plan = preempt(&problem([Bin::new([10, 10])], items), &PackOptions::new([0.5, 0.5])).unwrap();
+    assert_eq!(plan.result.bin_of("high"), Some(0));
+    // Neither evicts the other: "low" comes after "high", and "same" has the
+    // same priority.
+    assert!(plan.evictions.is_empty());
+    let unplaced: Vec<&str> = plan.result.unplaced.iter().map(|u| u.item.id.as_str()).collect();
+    assert_eq!(unplaced, ["same", "low"]);
+    assert!(plan.result.unplaced.iter().all(|u| u.reason == UnplacedReason::NoBinAvailable));
+}
+
+#[test]
+fn an_evicted_item_waits_for_higher_priorities_before_moving() {
+    let fleet = [
+        Bin::new([10, 10]).with_items([Item::new("pinned-0", [5, 5]).with_priority(100), Item::new("batch", [5, 1])]),
+        Bin::new([10, 10]).with_items([Item::new("pinned-1", [4, 6]).with_priority(100)]),
+    ];
+    // "db" only fits in bin 0 once "batch" is evicted. On its own "batch"
+    // then moves to bin 1, but "web" outranks it and takes the room first.
+    let db = Item::new("db", [1, 5]).with_priority(10);
+    let plan = preempt(&problem(fleet.clone(), [db.clone()]), &PackOptions::new([0.5, 0.5])).unwrap();
+    assert_eq!(plan.evictions[0].rescheduled_to, Some(1));
+    assert_eq!(plan.result.bin_of("batch"), Some(1));
+
+    let plan = preempt(&problem(fleet, [db, Item::new("web", [3, 3]).with_priority(5)]), &PackOptions::new([0.5, 0.5])).unwrap();
+    assert_eq!((plan.result.bin_of("db"), plan.result.bin_of("web"), plan.result.bin_of("batch")), (Some(0), Some(1), None));
+    assert_eq!(plan.evictions.len(), 1);
+    let eviction = &plan.evictions[0];
+    assert_eq!((eviction.item.id.as_str(), eviction.from, eviction.preempted_by.as_str(), eviction.rescheduled_to), ("batch", 0, "db", None));
+    assert_eq!(plan.stopped().count(), 1);
+}
+
+// SplitMix64, enough for generating instances.
+struct Rng(u64);
+
+impl Rng {
+    fn below(&mut self, n: u64) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        (z ^ (z >> 31)) % n
+    }
+}
+
+#[test]
+fn every_evicted_item_is_reported_once_where_it_ends_up() {
+    let mut rng = Rng(5);
+    let mut evicted = 0;
+    for case in 0..300 {
+        let demand = |rng: &mut Rng| [1 + rng.below(6) as u32, 1 + rng.below(6) as u32];
+        let fleet: Vec<Bin> = (0..1 + rng.below(4))
+            .map(|b| {
+                let items: Vec<Item> = (0..rng.below(4)).map(|k| Item::new(format!("held-{}-{}", b, k), demand(&mut rng)).with_priority(rng.below(4) as i32)).collect();
+                let mut bin = Bin::new([10, 10]);
+                for item in items {
+                    if bin.fits(&item, &PackOptions::new([0.5, 0.5])) {
+                        bin.push_item(item);
+                    }
+                }
+                bin
+            })
+            .collect();
+        let items: Vec<Item> = (0..1 + rng.below(6)).map(|i| Item::new(format!("new-{}", i), demand(&mut rng)).with_priority(rng.below(6) as i32)).collect();
+        let plan = preempt(&problem(fleet.clone(), items), &PackOptions::new([0.5, 0.5])).unwrap();
+
+        let mut ids: Vec<&str> = plan.evictions.iter().map(|e| e.item.id.as_str()).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), plan.evictions.len(), "case {}", case);
+        for eviction in &plan.evictions {
+            assert!(fleet[eviction.from].items().iter().any(|item| item.id == eviction.item.id), "case {}", case);
+            assert_eq!(plan.result.bin_of(&eviction.item.id), eviction.rescheduled_to, "case {}", case);
+        }
+        assert_eq!(plan.result.bins.len(), fleet.len());
+        evicted += plan.evictions.len();
+    }
+    assert!(evicted > 0);
+}
+
+#[test]
+fn nothing_is_evicted_while_an_existing_bin_has_room() {
+    let fleet = [Bin::new([10, 10]).with_items([Item::new("batch", [8, 8])]), Bin::new([10, 10])];
+    let plan = preempt(&problem(fleet, [Item::new("db", [8, 8]).with_priority(10)]), &PackOptions::new([0.5, 0.5])).unwrap();
+    assert_eq!(plan.result.bin_of("db"), Some(1));
+    assert!(plan.evictions.is_empty());
+    assert_eq!((plan.result.existing_bins, plan.result.bins_used(), plan.result.cost), (2, 0, 0.0));
+}
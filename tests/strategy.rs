@@ -0,0 +1,241 @@
+// The built-in strategies look bins up in an index rather than trying every
+// bin. These tests check, on random instances, that every lookup gives the
+// bin a plain scan of `PlacementContext::candidates` would, and that a
+// strategy selecting a bin the item cannot go into is caught.
+
+use std::cmp::Reverse;
+
+use bin_packer::{
+    pack, pack_fixed, preempt, AlmostWorstFit, Bin, BinType, BestFit, Constraint, FirstFit, Item, Maximise, NextFit, PackError, PackOptions, Packer,
+    PlacementContext, PlacementStrategy, Problem, ResourceSchema, Topology, WorstFit,
+};
+
+// SplitMix64, enough for generating instances.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn zone(&mut self) -> &'static str {
+        ["a", "b", "c"][self.below(3) as usize]
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Next,
+    First,
+    Best,
+    Worst,
+    AlmostWorst,
+}
+
+// Runs the built-in strategy of `kind` and checks its choice against a scan.
+#[derive(Debug)]
+struct Checked(Kind);
+
+impl PlacementStrategy for Checked {
+    fn select(&self, ctx: &PlacementContext<'_>) -> Option<usize> {
+        let selected = match self.0 {
+            Kind::Next => NextFit.select(ctx),
+            Kind::First => FirstFit.select(ctx),
+            Kind::Best => BestFit.select(ctx),
+            Kind::Worst => WorstFit.select(ctx),
+            Kind::AlmostWorst => AlmostWorstFit.select(ctx),
+        };
+        assert_eq!(selected, scan(self.0, ctx), "{:?} placing {:?}", self.0, ctx.item().id);
+        selected
+    }
+}
+
+// The bin `kind` should choose, by trying every candidate.
+fn scan(kind: Kind, ctx: &PlacementContext<'_>) -> Option<usize> {
+    let candidates: Vec<usize> = ctx.candidates().collect();
+    let violations = |b: usize| ctx.spread_violations(b);
+    let rank = |b: usize| (violations(b), Reverse(ctx.preference(b)));
+    let least_slack = |b: usize| (rank(b), ctx.slack(b), b);
+    let most_slack = |bins: &mut dyn Iterator<Item = usize>| {
+        bins.min_by(|&x, &y| rank(x).cmp(&rank(y)).then(ctx.slack(y).total_cmp(&ctx.slack(x))).then(x.cmp(&y)))
+    };
+    match kind {
+        Kind::Next => {
+            let from = ctx.current().unwrap_or(0);
+            candidates.iter().copied().filter(|&b| b >= from).min_by_key(|&b| (violations(b), b))
+        }
+        Kind::First => candidates.iter().copied().min_by_key(|&b| (violations(b), b)),
+        Kind::Best => candidates.iter().copied().min_by(|&x, &y| least_slack(x).partial_cmp(&least_slack(y)).unwrap()),
+        Kind::Worst => most_slack(&mut candidates.iter().copied()),
+        Kind::AlmostWorst => {
+            let worst = most_slack(&mut candidates.iter().copied())?;
+            most_slack(&mut candidates.iter().copied().filter(|&b| b != worst)).or(Some(worst))
+        }
+    }
+}
+
+// A problem with labelled bin types, existing bins with reserved usage and
+// items, preferred labels and preferred spread constraints.
+fn instance(rng: &mut Rng, dimensions: usize) -> Problem {
+    let schema = ResourceSchema::new((0..dimensions).map(|d| format!("r{}", d)));
+    let capacity = |rng: &mut Rng| (0..dimensions).map(|_| 20 + rng.below(30) as u32).collect::<Vec<u32>>();
+    let demand = |rng: &mut Rng, cap: u64| (0..dimensions).map(|_| 1 + rng.below(cap) as u32).collect::<Vec<u32>>();
+
+    let bin_types: Vec<BinType> = (0..1 + rng.below(3))
+        .map(|t| BinType::new(format!("type-{}", t), capacity(rng)).with_label("zone", rng.zone()))
+        .collect();
+    let existing: Vec<Bin> = (0..rng.below(12))
+        .map(|e| {
+            let capacity = capacity(rng);
+            let reserved: Vec<u32> = capacity.iter().map(|&c| rng.below(c as u64 / 2) as u32).collect();
+            let items: Vec<Item> = (0..rng.below(3)).map(|k| Item::new(format!("held-{}-{}", e, k), demand(rng, 6))).collect();
+            Bin::with_reserved(capacity, reserved).with_label("zone", rng.zone()).with_items(items)
+        })
+        .collect();
+    let items: Vec<Item> = (0..10 + rng.below(80))
+        .map(|i| {
+            let item = Item::new(format!("item-{}", i), demand(rng, 18));
+            match rng.below(3) {
+                0 => item.with_preferred_label("zone", rng.zone()),
+                _ => item,
+            }
+        })
+        .collect();
+    let spread: Vec<String> = items.iter().filter(|_| rng.below(4) == 0).map(|item| item.id.clone()).collect();
+
+    let mut problem = Problem::from_bin_types(schema, bin_types).with_existing_bins(existing).with_items(items);
+    if rng.below(2) == 0 && !spread.is_empty() {
+        problem = problem.with_topology(Topology::new(["zone"])).with_constraints([Constraint::prefer_spread(spread, "zone", 1)]);
+    }
+    problem
+}
+
+// Weighted, strict and overcommitted options with random weights.
+fn modes(rng: &mut Rng, dimensions: usize) -> [PackOptions; 3] {
+    let weights: Vec<f32> = (0..dimensions).map(|_| 1.0 + rng.below(9) as f32).collect();
+    let ratios: Vec<f32> = (0..dimensions).map(|_| 1.0 + rng.below(6) as f32 / 10.0).collect();
+    [
+        PackOptions::weighted(weights.clone()),
+        PackOptions::new(weights.clone()),
+        PackOptions::new(weights).with_overcommit(ratios),
+    ]
+}
+
+const KINDS: [Kind; 5] = [Kind::Next, Kind::First, Kind::Best, Kind::Worst, Kind::AlmostWorst];
+
+#[test]
+fn indexed_lookups_match_a_scan_when_packing() {
+    let mut rng = Rng(7);
+    for _ in 0..120 {
+        let dimensions = 1 + rng.below(3) as usize;
+        let problem = instance(&mut rng, dimensions);
+        for options in modes(&mut rng, dimensions) {
+            for kind in KINDS {
+                pack(&problem, &options.clone().with_strategy(Checked(kind))).unwrap();
+            }
+        }
+    }
+}
+
+// With a single bin size many bins are left with the same slack, which the
+// best- and worst-fit lookups skip over rather than trying each.
+#[test]
+fn indexed_lookups_match_a_scan_with_bins_of_one_size() {
+    let mut rng = Rng(13);
+    for _ in 0..40 {
+        let dimensions = 1 + rng.below(3) as usize;
+        let schema = ResourceSchema::new((0..dimensions).map(|d| format!("r{}", d)));
+        let items: Vec<Item> = (0..100 + rng.below(300))
+            .map(|i| Item::new(format!("item-{}", i), (0..dimensions).map(|_| 1 + rng.below(8) as u32).collect::<Vec<u32>>()))
+            .collect();
+        let problem = Problem::new(schema, vec![16; dimensions]).with_items(items);
+        for options in modes(&mut rng, dimensions) {
+            for kind in KINDS {
+                pack(&problem, &options.clone().with_strategy(Checked(kind))).unwrap();
+            }
+        }
+    }
+}
+
+#[test]
+fn indexed_lookups_match_a_scan_when_inserting_and_removing() {
+    let mut rng = Rng(11);
+    for _ in 0..60 {
+        let dimensions = 1 + rng.below(3) as usize;
+        let problem = instance(&mut rng, dimensions);
+        for options in modes(&mut rng, dimensions) {
+            for kind in KINDS {
+                let mut packer = Packer::new(&problem, &options.clone().with_strategy(Checked(kind))).unwrap();
+                let mut placed = Vec::new();
+                for item in &problem.items {
+                    if packer.insert(item.clone()).is_ok() {
+                        placed.push(item.id.clone());
+                    }
+                    if rng.below(3) == 0 && !placed.is_empty() {
+                        let id = placed.swap_remove(rng.below(placed.len() as u64) as usize);
+                        packer.remove(&id);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Always selects the same bin, whether or not the item fits in it.
+#[derive(Debug)]
+struct Always(usize);
+
+impl PlacementStrategy for Always {
+    fn select(&self, ctx: &PlacementContext<'_>) -> Option<usize> {
+        (!ctx.bins().is_empty()).then_some(self.0)
+    }
+}
+
+#[test]
+fn a_strategy_selecting_a_bin_the_item_cannot_go_into_is_an_error() {
+    let schema = ResourceSchema::new(["cores", "memory_gb"]);
+    let items = [Item::new("a", [8, 8]), Item::new("b", [8, 8])];
+    let problem = Problem::new(schema.clone(), [10, 10]).with_items(items.clone());
+    let options = PackOptions::new([0.5, 0.5]).with_strategy(Always(0));
+    let invalid = PackError::InvalidStrategy { item: "b".to_string(), bin: 0 };
+    assert_eq!(pack(&problem, &options), Err(invalid.clone()));
+
+    let mut packer = Packer::new(&problem, &options).unwrap();
+    assert_eq!(packer.insert(items[0].clone()), Ok(0));
+    assert_eq!(packer.insert(items[1].clone()), Err(invalid));
+    assert_eq!(packer.len(), 1);
+
+    // Out of range.
+    let options = PackOptions::new([0.5, 0.5]).with_strategy(Always(5));
+    assert_eq!(pack(&problem, &options), Err(PackError::InvalidStrategy { item: "b".to_string(), bin: 5 }));
+}
+
+#[test]
+fn a_strategy_selecting_a_bin_the_constraints_forbid_is_an_error() {
+    let items = [Item::new("a", [2, 2]), Item::new("b", [2, 2])];
+    let problem = Problem::new(ResourceSchema::new(["cores", "memory_gb"]), [10, 10])
+        .with_items(items)
+        .with_constraints([Constraint::anti_affinity(["a", "b"])]);
+    let options = PackOptions::new([0.5, 0.5]).with_strategy(Always(0));
+    assert_eq!(pack(&problem, &options), Err(PackError::InvalidStrategy { item: "b".to_string(), bin: 0 }));
+    assert_eq!(pack(&problem, &PackOptions::new([0.5, 0.5])).unwrap().bins_used(), 2);
+}
+
+#[test]
+fn preemption_and_fixed_fleets_check_the_strategy_too() {
+    let schema = ResourceSchema::new(["cores", "memory_gb"]);
+    let items = [Item::new("a", [8, 8]), Item::new("b", [8, 8])];
+    let options = PackOptions::new([0.5, 0.5]).with_strategy(Always(0));
+    let fleet = Problem::new(schema.clone(), [10, 10]).with_existing_bins([Bin::new([10, 10]), Bin::new([10, 10])]).with_items(items.clone());
+    assert!(matches!(preempt(&fleet, &options), Err(PackError::InvalidStrategy { .. })));
+    let fleet = Problem::from_bin_types(schema, vec![BinType::new("small", [10, 10]).with_max_count(2)]).with_items(items);
+    assert!(matches!(pack_fixed(&fleet, &options, Maximise::Value), Err(PackError::InvalidStrategy { .. })));
+}
@@ -0,0 +1,10 @@
+use bin_packer::{pack, sweep, Item, MultiStartOptions, PackError, PackOptions, Problem, ResourceSchema};
+
+#[test]
+fn sweep_rejects_items_with_too_few_dimensions() {
+    let problem = Problem::new(ResourceSchema::new(["cores", "disk_gb"]), [10, 200]).with_items([Item::new("short", [4])]);
+    let options = PackOptions::new([0.5, 0.5]);
+    let grid = MultiStartOptions::default().with_weight_grid(2, 4);
+    assert!(matches!(pack(&problem, &options), Err(PackError::DimensionMismatch { .. })));
+    assert!(matches!(sweep(&problem, &options, &grid), Err(PackError::DimensionMismatch { .. })));
+}
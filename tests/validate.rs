@@ -0,0 +1,15 @@
+use bin_packer::{pack, Bin, Item, PackError, PackOptions, Packer, Problem, ResourceSchema};
+
+#[test]
+fn an_existing_bin_without_capacity_is_rejected() {
+    let problem = Problem::new(ResourceSchema::new(["cores", "memory_gb"]), [8, 8])
+        .with_existing_bins([Bin::new([8, 8]), Bin::new([8, 0])])
+        .with_items([Item::new("web", [1, 1])]);
+    let zero = PackError::ZeroBinCapacity {
+        bin: 1,
+        dimension: "memory_gb".to_string(),
+    };
+    assert_eq!(zero.to_string(), "existing bin 1 memory_gb capacity must be positive");
+    assert_eq!(pack(&problem, &PackOptions::new([0.5, 0.5])), Err(zero.clone()));
+    assert_eq!(Packer::new(&Problem { items: Vec::new(), ..problem }, &PackOptions::new([0.5, 0.5])).err(), Some(zero));
+}